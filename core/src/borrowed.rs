@@ -0,0 +1,44 @@
+//! A borrowed version of `Ipld`.
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use crate::{Cid, Ipld};
+
+/// Like [`Ipld`], but strings and bytes point into the data it was decoded from.
+///
+/// Decoding into an `IpldRef` only allocates for lists, maps and the digests of links, never for
+/// strings or bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IpldRef<'a> {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    Float(f64),
+    String(&'a str),
+    Bytes(&'a [u8]),
+    List(Vec<IpldRef<'a>>),
+    Map(BTreeMap<&'a str, IpldRef<'a>>),
+    Link(Cid),
+}
+
+impl<'a> IpldRef<'a> {
+    /// Copies the value into an owned [`Ipld`].
+    pub fn to_owned(&self) -> Ipld {
+        match self {
+            IpldRef::Null => Ipld::Null,
+            IpldRef::Bool(value) => Ipld::Bool(*value),
+            IpldRef::Integer(value) => Ipld::Integer(*value),
+            IpldRef::Float(value) => Ipld::Float(*value),
+            IpldRef::String(value) => Ipld::String(value.to_string()),
+            IpldRef::Bytes(value) => Ipld::Bytes(value.to_vec()),
+            IpldRef::List(list) => Ipld::List(list.iter().map(IpldRef::to_owned).collect()),
+            IpldRef::Map(map) => Ipld::Map(
+                map.iter()
+                    .map(|(key, value)| (key.to_string(), value.to_owned()))
+                    .collect(),
+            ),
+            IpldRef::Link(value) => Ipld::Link(value.clone()),
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Content identifiers.
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::multibase;
+use crate::multihash::{self, Multihash};
+use crate::varint;
+
+/// The multicodec code of dag-pb, which is the codec of every CIDv0.
+pub const DAG_PB: u64 = 0x70;
+
+/// The version of a CID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Version {
+    /// A bare SHA2-256 multihash of a dag-pb block.
+    V0,
+    /// A version, a multicodec code and a multihash.
+    V1,
+}
+
+/// A content identifier.
+///
+/// The binary form is parsed on construction, so every `Cid` is structurally valid.
+///
+/// CIDs are ordered by their binary form.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Cid {
+    version: Version,
+    codec: u64,
+    hash: Multihash,
+}
+
+impl Cid {
+    /// Creates a CIDv0, the hash must be a SHA2-256 digest.
+    pub fn new_v0(hash: Multihash) -> Result<Self, CidError> {
+        if hash.code() != multihash::SHA2_256 || hash.digest().len() != 32 {
+            return Err(CidError::InvalidCidV0);
+        }
+        Ok(Self {
+            version: Version::V0,
+            codec: DAG_PB,
+            hash,
+        })
+    }
+
+    /// Creates a CIDv1 from the multicodec code of the content and its hash.
+    pub fn new_v1(codec: u64, hash: Multihash) -> Self {
+        Self {
+            version: Version::V1,
+            codec,
+            hash,
+        }
+    }
+
+    /// Returns the version.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns the multicodec code of the content.
+    pub fn codec(&self) -> u64 {
+        self.codec
+    }
+
+    /// Returns the hash of the content.
+    pub fn hash(&self) -> &Multihash {
+        &self.hash
+    }
+
+    /// Returns the binary form.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self.version {
+            Version::V0 => self.hash.to_bytes(),
+            Version::V1 => {
+                let mut bytes = Vec::new();
+                varint::write(1, &mut bytes);
+                varint::write(self.codec, &mut bytes);
+                bytes.extend_from_slice(&self.hash.to_bytes());
+                bytes
+            }
+        }
+    }
+
+    /// Returns the length of the binary form, without building it.
+    pub fn encoded_len(&self) -> usize {
+        let hash = varint::len(self.hash.code())
+            + varint::len(self.hash.digest().len() as u64)
+            + self.hash.digest().len();
+        match self.version {
+            Version::V0 => hash,
+            Version::V1 => varint::len(1) + varint::len(self.codec) + hash,
+        }
+    }
+
+    /// Returns the binary form behind the identity multibase prefix (0x00), the contents of a
+    /// DAG-CBOR link in tag 42.
+    pub fn link_bytes(&self) -> Vec<u8> {
+        let cid = self.to_bytes();
+        let mut bytes = Vec::with_capacity(1 + cid.len());
+        bytes.push(multibase::IDENTITY);
+        bytes.extend_from_slice(&cid);
+        bytes
+    }
+}
+
+impl PartialOrd for Cid {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares the fields in the order of the binary form, without building it.
+impl Ord for Cid {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.version, other.version) {
+            // A CIDv0 starts with the SHA2-256 code 0x12, a CIDv1 with its version 0x01.
+            (Version::V0, Version::V1) => Ordering::Greater,
+            (Version::V1, Version::V0) => Ordering::Less,
+            // The code and length of the digest are the same for every CIDv0.
+            (Version::V0, Version::V0) => self.hash.digest().cmp(other.hash.digest()),
+            (Version::V1, Version::V1) => varint::cmp(self.codec, other.codec)
+                .then_with(|| varint::cmp(self.hash.code(), other.hash.code()))
+                .then_with(|| {
+                    varint::cmp(
+                        self.hash.digest().len() as u64,
+                        other.hash.digest().len() as u64,
+                    )
+                })
+                .then_with(|| self.hash.digest().cmp(other.hash.digest())),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for Cid {
+    type Error = CidError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        // A CIDv0 is a multihash, which for SHA2-256 starts with `0x12 0x20`.
+        if bytes.len() == 34 && bytes[0] == 0x12 && bytes[1] == 0x20 {
+            return Self::new_v0(Multihash::try_from(bytes)?);
+        }
+        let (version, version_len) = varint::read(bytes).ok_or(CidError::InvalidVarint)?;
+        if version != 1 {
+            return Err(CidError::UnknownVersion(version));
+        }
+        let bytes = &bytes[version_len..];
+        let (codec, codec_len) = varint::read(bytes).ok_or(CidError::InvalidVarint)?;
+        let hash = Multihash::try_from(&bytes[codec_len..])?;
+        Ok(Self::new_v1(codec, hash))
+    }
+}
+
+impl TryFrom<Vec<u8>> for Cid {
+    type Error = CidError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(&bytes[..])
+    }
+}
+
+/// Converts a CID of the `cid` crate, available with the `cid` feature.
+#[cfg(feature = "cid")]
+impl From<::cid::Cid> for Cid {
+    fn from(cid: ::cid::Cid) -> Self {
+        let hash = Multihash::new(cid.hash().code(), cid.hash().digest().to_vec());
+        match cid.version() {
+            ::cid::Version::V0 => {
+                Self::new_v0(hash).expect("the cid crate only creates SHA2-256 CIDv0s")
+            }
+            ::cid::Version::V1 => Self::new_v1(cid.codec(), hash),
+        }
+    }
+}
+
+/// Converts into a CID of the `cid` crate, available with the `cid` feature.
+///
+/// Fails if the digest is longer than the 64 bytes the `cid` crate supports.
+#[cfg(feature = "cid")]
+impl TryFrom<Cid> for ::cid::Cid {
+    type Error = ::cid::Error;
+
+    fn try_from(cid: Cid) -> Result<Self, Self::Error> {
+        Self::try_from(&cid.to_bytes()[..])
+    }
+}
+
+/// Formats the string form of the CID.
+///
+/// A CIDv0 is encoded as base58btc without a multibase prefix (`Qm…`), a CIDv1 as base32
+/// (`b…`).
+impl fmt::Display for Cid {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.version {
+            Version::V0 => fmt.write_str(&multibase::encode_base58(&self.to_bytes())),
+            Version::V1 => write!(
+                fmt,
+                "{}{}",
+                multibase::BASE32,
+                multibase::encode_base32(&self.to_bytes())
+            ),
+        }
+    }
+}
+
+/// Formats the string form as well, e.g. `Cid(bafy…)`, which is easier to compare with the
+/// output of other tools than the raw bytes.
+impl fmt::Debug for Cid {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "Cid({})", self)
+    }
+}
+
+/// Parses the string form of a CID.
+///
+/// Accepts CIDv0 strings (`Qm…`) and CIDv1 strings in base32 (`b…` or `B…`), base58btc
+/// (`z…`) or base16 (`f…` or `F…`). An unsupported prefix is a
+/// [`CidError::UnknownMultibase`], characters outside the alphabet of the encoding a
+/// [`CidError::InvalidBaseEncoding`] and all other errors are about the decoded bytes.
+impl FromStr for Cid {
+    type Err = CidError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if string.len() == 46 && string.starts_with("Qm") {
+            let bytes = multibase::decode_base58(string).ok_or(CidError::InvalidBaseEncoding)?;
+            return Self::try_from(bytes);
+        }
+        let mut chars = string.chars();
+        let bytes = match chars.next() {
+            Some(multibase::BASE32) | Some('B') => multibase::decode_base32(chars.as_str()),
+            Some(multibase::BASE58BTC) => multibase::decode_base58(chars.as_str()),
+            Some(multibase::BASE16) | Some('F') => multibase::decode_base16(chars.as_str()),
+            Some(prefix) => return Err(CidError::UnknownMultibase(prefix)),
+            None => None,
+        };
+        Self::try_from(bytes.ok_or(CidError::InvalidBaseEncoding)?)
+    }
+}
+
+/// Errors when parsing the binary or string form of a CID or multihash.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CidError {
+    /// A varint is truncated, too long or not minimally encoded.
+    InvalidVarint,
+    /// The CID version isn't known.
+    UnknownVersion(u64),
+    /// The length of a multihash digest doesn't match the length it declares.
+    InvalidDigestLength {
+        /// The declared length.
+        expected: u64,
+        /// The number of bytes that are actually there.
+        actual: usize,
+    },
+    /// A CIDv0 needs to be a SHA2-256 hash.
+    InvalidCidV0,
+    /// The multibase prefix of a CID string isn't supported.
+    UnknownMultibase(char),
+    /// A CID string isn't valid in the encoding its prefix declares.
+    InvalidBaseEncoding,
+}
+
+impl fmt::Display for CidError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CidError::InvalidVarint => write!(fmt, "invalid varint"),
+            CidError::UnknownVersion(version) => write!(fmt, "unknown CID version {}", version),
+            CidError::InvalidDigestLength { expected, actual } => write!(
+                fmt,
+                "multihash digest should be {} bytes long, but is {}",
+                expected, actual
+            ),
+            CidError::InvalidCidV0 => write!(fmt, "a CIDv0 must be a SHA2-256 hash"),
+            CidError::UnknownMultibase(prefix) => {
+                write!(fmt, "unsupported multibase prefix {:?}", prefix)
+            }
+            CidError::InvalidBaseEncoding => write!(fmt, "invalid multibase encoding"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CidError {}
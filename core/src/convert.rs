@@ -0,0 +1,119 @@
+//! Converting `Ipld` values into Rust types.
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::{Cid, Ipld};
+
+/// Why an `Ipld` value couldn't be converted into a Rust type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The value is of another variant than the type needs.
+    WrongVariant {
+        /// The variant the type is converted from, e.g. `"Integer"`.
+        expected: &'static str,
+        /// The variant of the value.
+        found: &'static str,
+    },
+    /// An integer doesn't fit into the integer type.
+    IntegerOutOfRange {
+        /// The integer.
+        value: i128,
+        /// The integer type, e.g. `"u64"`.
+        target: &'static str,
+    },
+}
+
+impl ConversionError {
+    /// The error for converting `found` into a type that needs the `expected` variant.
+    pub(crate) fn wrong_variant(expected: &'static str, found: &Ipld) -> Self {
+        ConversionError::WrongVariant {
+            expected,
+            found: found.variant_name(),
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::WrongVariant { expected, found } => {
+                write!(fmt, "expected {}, found {}", expected, found)
+            }
+            ConversionError::IntegerOutOfRange { value, target } => {
+                write!(fmt, "integer {} is out of range for {}", value, target)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConversionError {}
+
+impl Ipld {
+    /// The name of the variant, used in errors.
+    fn variant_name(&self) -> &'static str {
+        match self {
+            Ipld::Null => "Null",
+            Ipld::Bool(_) => "Bool",
+            Ipld::Integer(_) => "Integer",
+            Ipld::Float(_) => "Float",
+            Ipld::String(_) => "String",
+            Ipld::Bytes(_) => "Bytes",
+            Ipld::List(_) => "List",
+            Ipld::Map(_) => "Map",
+            Ipld::Link(_) => "Link",
+        }
+    }
+}
+
+macro_rules! try_from_variant {
+    ($($ty:ty => $variant:ident,)*) => {
+        $(
+            impl TryFrom<Ipld> for $ty {
+                type Error = ConversionError;
+
+                fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+                    match ipld {
+                        Ipld::$variant(value) => Ok(value),
+                        other => Err(ConversionError::wrong_variant(stringify!($variant), &other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+try_from_variant! {
+    bool => Bool,
+    i128 => Integer,
+    f64 => Float,
+    String => String,
+    Vec<u8> => Bytes,
+    Vec<Ipld> => List,
+    BTreeMap<String, Ipld> => Map,
+    Cid => Link,
+}
+
+macro_rules! try_from_integer {
+    ($($ty:ident),*) => {
+        $(
+            /// Fails if the integer doesn't fit, it is never truncated.
+            impl TryFrom<Ipld> for $ty {
+                type Error = ConversionError;
+
+                fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+                    let value = i128::try_from(ipld)?;
+                    $ty::try_from(value).map_err(|_| ConversionError::IntegerOutOfRange {
+                        value,
+                        target: stringify!($ty),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+try_from_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
@@ -0,0 +1,90 @@
+//! Formatting `Ipld` values in CBOR diagnostic notation.
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use crate::Ipld;
+
+/// Formats the value in the diagnostic notation of RFC 8949, section 8, as it would be encoded
+/// in DAG-CBOR.
+///
+/// Strings are quoted and escaped like in JSON, bytes are written as `h'0102'` and links as tag
+/// 42 around the CID bytes with the multibase identity prefix, e.g. `42(h'00017112…')`. Map
+/// entries are written in the canonical DAG-CBOR key order, shorter keys first. Floats always
+/// have a fraction or an exponent and are written with as many digits as needed to parse them
+/// back to the same value, non-finite ones as `NaN`, `Infinity` and `-Infinity`.
+impl fmt::Display for Ipld {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ipld::Null => fmt.write_str("null"),
+            Ipld::Bool(value) => write!(fmt, "{}", value),
+            Ipld::Integer(value) => write!(fmt, "{}", value),
+            Ipld::Float(value) if value.is_nan() => fmt.write_str("NaN"),
+            Ipld::Float(value) if value.is_infinite() => fmt.write_str(if *value > 0.0 {
+                "Infinity"
+            } else {
+                "-Infinity"
+            }),
+            Ipld::Float(value) => write!(fmt, "{:?}", value),
+            Ipld::String(string) => write_string(string, fmt),
+            Ipld::Bytes(bytes) => write_bytes(bytes, fmt),
+            Ipld::List(list) => {
+                fmt.write_char('[')?;
+                for (index, ipld) in list.iter().enumerate() {
+                    if index > 0 {
+                        fmt.write_str(", ")?;
+                    }
+                    write!(fmt, "{}", ipld)?;
+                }
+                fmt.write_char(']')
+            }
+            Ipld::Map(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by_key(|(key, _)| (key.len(), key.as_bytes()));
+                fmt.write_char('{')?;
+                for (index, (key, ipld)) in entries.into_iter().enumerate() {
+                    if index > 0 {
+                        fmt.write_str(", ")?;
+                    }
+                    write_string(key, fmt)?;
+                    write!(fmt, ": {}", ipld)?;
+                }
+                fmt.write_char('}')
+            }
+            Ipld::Link(link) => {
+                fmt.write_str("42(h'00")?;
+                write_hex(&link.to_bytes(), fmt)?;
+                fmt.write_str("')")
+            }
+        }
+    }
+}
+
+fn write_string(string: &str, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt.write_char('"')?;
+    for c in string.chars() {
+        match c {
+            '"' => fmt.write_str("\\\"")?,
+            '\\' => fmt.write_str("\\\\")?,
+            '\n' => fmt.write_str("\\n")?,
+            '\r' => fmt.write_str("\\r")?,
+            '\t' => fmt.write_str("\\t")?,
+            '\u{8}' => fmt.write_str("\\b")?,
+            '\u{c}' => fmt.write_str("\\f")?,
+            c if c.is_control() => write!(fmt, "\\u{:04x}", c as u32)?,
+            c => fmt.write_char(c)?,
+        }
+    }
+    fmt.write_char('"')
+}
+
+fn write_bytes(bytes: &[u8], fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt.write_str("h'")?;
+    write_hex(bytes, fmt)?;
+    fmt.write_char('\'')
+}
+
+fn write_hex(bytes: &[u8], fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    bytes
+        .iter()
+        .try_for_each(|byte| write!(fmt, "{:02x}", byte))
+}
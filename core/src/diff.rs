@@ -0,0 +1,100 @@
+//! Finding the differences between two `Ipld` values.
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::{self, Vec};
+use core::mem;
+
+use crate::{Ipld, Path};
+
+/// How a value differs, see [`diff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffKind {
+    /// The value is only in the second one.
+    Added,
+    /// The value is only in the first one.
+    Removed,
+    /// The values are of different variants, e.g. an `Integer` and a `Float`.
+    ChangedType,
+    /// The values are of the same variant, but not equal.
+    ChangedValue,
+}
+
+/// Returns the paths at which `b` differs from `a`, e.g. `meta/version`.
+///
+/// The paths are the string form of [`Path`] without its leading slash, segments are escaped the
+/// same way and they parse back into a `Path`. A difference between the values themselves is
+/// reported at the root path, the empty string.
+///
+/// Maps and lists are compared entry by entry, so a difference is reported at the deepest path
+/// it occurs. Map entries are visited in key order, list elements by index; elements that only
+/// one of two lists has are added or removed. Values are compared like `Ipld` values are, e.g.
+/// `0.0` and `-0.0` differ.
+pub fn diff(a: &Ipld, b: &Ipld) -> Vec<(String, DiffKind)> {
+    let mut differences = Vec::new();
+    // Like the iterators, the walk uses an explicit stack, so that deeply nested values don't
+    // overflow the call stack.
+    let mut segments = Vec::new();
+    let mut stack = Vec::new();
+    match children(a, b) {
+        Some(children) => stack.push(children),
+        None => compare(a, b, &segments, &mut differences),
+    }
+    while let Some(pending) = stack.last_mut() {
+        let (segment, a, b) = match pending.next() {
+            Some(child) => child,
+            None => {
+                stack.pop();
+                continue;
+            }
+        };
+        segments.truncate(stack.len() - 1);
+        segments.push(segment);
+        match (a, b) {
+            (Some(a), Some(b)) => match children(a, b) {
+                Some(children) => stack.push(children),
+                None => compare(a, b, &segments, &mut differences),
+            },
+            (Some(_), None) => differences.push((path(&segments), DiffKind::Removed)),
+            (None, Some(_)) => differences.push((path(&segments), DiffKind::Added)),
+            (None, None) => {}
+        }
+    }
+    differences
+}
+
+/// The entries or elements at the same keys or indices, either may be missing.
+type Children<'a> = vec::IntoIter<(String, Option<&'a Ipld>, Option<&'a Ipld>)>;
+
+/// Returns the children to compare if both values are maps or both are lists.
+fn children<'a>(a: &'a Ipld, b: &'a Ipld) -> Option<Children<'a>> {
+    let children: Vec<_> = match (a, b) {
+        (Ipld::Map(a), Ipld::Map(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            keys.into_iter()
+                .map(|key| (key.clone(), a.get(key), b.get(key)))
+                .collect()
+        }
+        (Ipld::List(a), Ipld::List(b)) => (0..a.len().max(b.len()))
+            .map(|index| (index.to_string(), a.get(index), b.get(index)))
+            .collect(),
+        _ => return None,
+    };
+    Some(children.into_iter())
+}
+
+/// Compares two values that aren't both maps or both lists.
+fn compare(a: &Ipld, b: &Ipld, segments: &[String], differences: &mut Vec<(String, DiffKind)>) {
+    if a != b {
+        let kind = if mem::discriminant(a) == mem::discriminant(b) {
+            DiffKind::ChangedValue
+        } else {
+            DiffKind::ChangedType
+        };
+        differences.push((path(segments), kind));
+    }
+}
+
+fn path(segments: &[String]) -> String {
+    let path = Path::from_segments(segments.iter().cloned()).to_string();
+    path.strip_prefix('/').map(String::from).unwrap_or(path)
+}
@@ -0,0 +1,68 @@
+//! Keys and list indices for looking up values.
+use alloc::string::String;
+
+use crate::Ipld;
+
+/// Something that can look up a value inside an `Ipld`: a map key or a list index.
+///
+/// Implemented for `str`, `String`, `usize` and references to them. Looking up a key in
+/// something other than a map or an index in something other than a list returns `None`.
+pub trait IpldIndex {
+    /// Returns the value this index points to.
+    fn index_into<'a>(&self, ipld: &'a Ipld) -> Option<&'a Ipld>;
+
+    /// Returns a mutable reference to the value this index points to.
+    fn index_into_mut<'a>(&self, ipld: &'a mut Ipld) -> Option<&'a mut Ipld>;
+}
+
+impl IpldIndex for str {
+    fn index_into<'a>(&self, ipld: &'a Ipld) -> Option<&'a Ipld> {
+        match ipld {
+            Ipld::Map(map) => map.get(self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'a>(&self, ipld: &'a mut Ipld) -> Option<&'a mut Ipld> {
+        match ipld {
+            Ipld::Map(map) => map.get_mut(self),
+            _ => None,
+        }
+    }
+}
+
+impl IpldIndex for String {
+    fn index_into<'a>(&self, ipld: &'a Ipld) -> Option<&'a Ipld> {
+        self.as_str().index_into(ipld)
+    }
+
+    fn index_into_mut<'a>(&self, ipld: &'a mut Ipld) -> Option<&'a mut Ipld> {
+        self.as_str().index_into_mut(ipld)
+    }
+}
+
+impl IpldIndex for usize {
+    fn index_into<'a>(&self, ipld: &'a Ipld) -> Option<&'a Ipld> {
+        match ipld {
+            Ipld::List(list) => list.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'a>(&self, ipld: &'a mut Ipld) -> Option<&'a mut Ipld> {
+        match ipld {
+            Ipld::List(list) => list.get_mut(*self),
+            _ => None,
+        }
+    }
+}
+
+impl<T: IpldIndex + ?Sized> IpldIndex for &T {
+    fn index_into<'a>(&self, ipld: &'a Ipld) -> Option<&'a Ipld> {
+        (**self).index_into(ipld)
+    }
+
+    fn index_into_mut<'a>(&self, ipld: &'a mut Ipld) -> Option<&'a mut Ipld> {
+        (**self).index_into_mut(ipld)
+    }
+}
@@ -0,0 +1,234 @@
+//! Iterators over `Ipld` values.
+use alloc::string::{String, ToString};
+use alloc::vec::{self, Vec};
+use core::iter::Enumerate;
+use core::slice;
+
+use crate::{Cid, Ipld, Path};
+
+/// Where a child is within its parent.
+enum Segment<'a> {
+    Index(usize),
+    Key(&'a str),
+}
+
+/// The children of a list or map that are still to be visited.
+enum Children<'a> {
+    List(Enumerate<slice::Iter<'a, Ipld>>),
+    Map(vec::IntoIter<(&'a String, &'a Ipld)>),
+}
+
+impl<'a> Children<'a> {
+    /// Returns the children of lists and maps, `None` for all other values.
+    fn of(ipld: &'a Ipld) -> Option<Self> {
+        match ipld {
+            Ipld::List(list) => Some(Children::List(list.iter().enumerate())),
+            Ipld::Map(map) => Some(Children::Map(canonical_order(map.iter()))),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the entries of a map in the order DAG-CBOR encodes them, shorter keys first.
+///
+/// A `BTreeMap` already sorts keys of the same length bytewise, a stable sort by length is all
+/// that is left to do.
+fn canonical_order<'a, V>(
+    entries: impl Iterator<Item = (&'a String, V)>,
+) -> vec::IntoIter<(&'a String, V)> {
+    let mut entries: Vec<_> = entries.collect();
+    entries.sort_by_key(|(key, _)| key.len());
+    entries.into_iter()
+}
+
+impl<'a> Segment<'a> {
+    fn to_path_segment(&self) -> String {
+        match self {
+            Segment::Index(index) => index.to_string(),
+            Segment::Key(key) => key.to_string(),
+        }
+    }
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = (Segment<'a>, &'a Ipld);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Children::List(iter) => iter
+                .next()
+                .map(|(index, ipld)| (Segment::Index(index), ipld)),
+            Children::Map(iter) => iter.next().map(|(key, ipld)| (Segment::Key(key), ipld)),
+        }
+    }
+}
+
+/// An iterator over every link in a value, returned by [`Ipld::links`].
+pub struct Links<'a> {
+    /// The top-level value until it is visited.
+    root: Option<&'a Ipld>,
+    stack: Vec<Children<'a>>,
+}
+
+impl<'a> Links<'a> {
+    pub(crate) fn new(ipld: &'a Ipld) -> Self {
+        Self {
+            root: Some(ipld),
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Iterator for Links<'a> {
+    type Item = &'a Cid;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ipld = match self.root.take() {
+                Some(ipld) => ipld,
+                None => match self.stack.last_mut()?.next() {
+                    Some((_, ipld)) => ipld,
+                    None => {
+                        self.stack.pop();
+                        continue;
+                    }
+                },
+            };
+            if let Ipld::Link(link) = ipld {
+                return Some(link);
+            }
+            self.stack.extend(Children::of(ipld));
+        }
+    }
+}
+
+/// An iterator over every value and its path, returned by [`Ipld::iter`].
+pub struct Iter<'a> {
+    /// The top-level value until it is visited.
+    root: Option<&'a Ipld>,
+    stack: Vec<Children<'a>>,
+    /// The path of the value returned last, truncated to its parent before each step.
+    path: Vec<String>,
+}
+
+impl<'a> Iter<'a> {
+    pub(crate) fn new(ipld: &'a Ipld) -> Self {
+        Self {
+            root: Some(ipld),
+            stack: Vec::new(),
+            path: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (Path, &'a Ipld);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            self.stack.extend(Children::of(root));
+            return Some((Path::default(), root));
+        }
+        loop {
+            let depth = self.stack.len();
+            match self.stack.last_mut()?.next() {
+                Some((segment, ipld)) => {
+                    self.path.truncate(depth - 1);
+                    self.path.push(segment.to_path_segment());
+                    self.stack.extend(Children::of(ipld));
+                    return Some((Path::from_segments(self.path.iter()), ipld));
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// What [`Ipld::walk_mut`] does after the callback returns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WalkControl {
+    /// Walk the children of the value, then carry on with its siblings.
+    Continue,
+    /// Don't walk the children of the value, but carry on with its siblings.
+    SkipChildren,
+    /// End the walk.
+    Stop,
+}
+
+/// Like [`Children`], but for mutable references.
+enum ChildrenMut<'a> {
+    List(Enumerate<slice::IterMut<'a, Ipld>>),
+    Map(vec::IntoIter<(&'a String, &'a mut Ipld)>),
+}
+
+impl<'a> ChildrenMut<'a> {
+    fn of(ipld: &'a mut Ipld) -> Option<Self> {
+        match ipld {
+            Ipld::List(list) => Some(ChildrenMut::List(list.iter_mut().enumerate())),
+            Ipld::Map(map) => Some(ChildrenMut::Map(canonical_order(map.iter_mut()))),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Iterator for ChildrenMut<'a> {
+    type Item = (Segment<'a>, &'a mut Ipld);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ChildrenMut::List(iter) => iter
+                .next()
+                .map(|(index, ipld)| (Segment::Index(index), ipld)),
+            ChildrenMut::Map(iter) => iter.next().map(|(key, ipld)| (Segment::Key(key), ipld)),
+        }
+    }
+}
+
+pub(crate) fn walk<F>(root: &Ipld, f: &mut F)
+where
+    F: FnMut(&Ipld),
+{
+    f(root);
+    let mut stack: Vec<_> = Children::of(root).into_iter().collect();
+    while let Some(children) = stack.last_mut() {
+        match children.next() {
+            Some((_, ipld)) => {
+                f(ipld);
+                stack.extend(Children::of(ipld));
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+}
+
+pub(crate) fn walk_mut<F>(root: &mut Ipld, mut f: F)
+where
+    F: FnMut(&Path, &mut Ipld) -> WalkControl,
+{
+    let mut path = Path::default();
+    let mut stack = Vec::new();
+    match f(&path, root) {
+        WalkControl::Continue => stack.extend(ChildrenMut::of(root)),
+        WalkControl::SkipChildren | WalkControl::Stop => return,
+    }
+    while let Some(children) = stack.last_mut() {
+        let (segment, ipld) = match children.next() {
+            Some(child) => child,
+            None => {
+                stack.pop();
+                continue;
+            }
+        };
+        path.truncate(stack.len() - 1);
+        path.push(segment.to_path_segment());
+        match f(&path, ipld) {
+            WalkControl::Continue => stack.extend(ChildrenMut::of(ipld)),
+            WalkControl::SkipChildren => {}
+            WalkControl::Stop => return,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Conversions between `Ipld` and `serde_json::Value`, available with the `json` feature.
+//!
+//! The conversions follow the DAG-JSON conventions: bytes are `{"/": {"bytes": "<base64>"}}`
+//! and links are `{"/": "<cid>"}`.
+use alloc::string::{String, ToString};
+use core::convert::TryFrom;
+use core::fmt;
+use core::str::FromStr;
+
+use serde_json::{Map, Number, Value};
+
+use crate::{multibase, Cid, CidError, Ipld};
+
+/// The largest integer that JSON parsers using 64-bit floats for numbers, like JavaScript's,
+/// read without losing precision, 2^53 - 1.
+pub const MAX_SAFE_INTEGER: i128 = (1 << 53) - 1;
+
+/// How `Bytes` and `Link` are converted into JSON, which has no type for either.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BytesAndLinks {
+    /// Use the DAG-JSON shapes, `{"/": {"bytes": "<base64>"}}` and `{"/": "<cid>"}`.
+    ///
+    /// This is lossy: converting back turns plain maps of the same shapes into bytes and links.
+    #[default]
+    DagJson,
+    /// Return an error.
+    Reject,
+}
+
+/// Options for converting an `Ipld` value into a `serde_json::Value`.
+#[derive(Clone, Debug, Default)]
+pub struct JsonOptions {
+    /// How `Bytes` and `Link` are converted.
+    pub bytes_and_links: BytesAndLinks,
+    /// Allow all integers between `i64::MIN` and `u64::MAX`, instead of only those within
+    /// [`MAX_SAFE_INTEGER`]. Many JSON parsers silently lose precision beyond it.
+    pub allow_unsafe_integers: bool,
+}
+
+/// Errors when converting between `Ipld` and `serde_json::Value`.
+#[derive(Debug, PartialEq)]
+pub enum JsonError {
+    /// An integer is outside the range the options allow. Without
+    /// [`JsonOptions::allow_unsafe_integers`] that is [`MAX_SAFE_INTEGER`], with it 64 bits,
+    /// which is the most `serde_json` can represent.
+    IntegerOutOfRange(i128),
+    /// `Bytes` or a `Link`, which [`BytesAndLinks::Reject`] doesn't allow.
+    NotAllowed(&'static str),
+    /// A float is NaN or infinite, which JSON can't represent.
+    NonFiniteFloat(f64),
+    /// A link isn't a valid CID.
+    InvalidLink(CidError),
+    /// The base64 of a `{"/": {"bytes": ...}}` object is invalid.
+    InvalidBytes,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::IntegerOutOfRange(value) => {
+                write!(
+                    fmt,
+                    "integer {} can't be represented exactly in JSON",
+                    value
+                )
+            }
+            JsonError::NotAllowed(kind) => write!(fmt, "{} can't be converted into JSON", kind),
+            JsonError::NonFiniteFloat(value) => {
+                write!(fmt, "{} can't be represented in JSON", value)
+            }
+            JsonError::InvalidLink(error) => write!(fmt, "invalid link: {}", error),
+            JsonError::InvalidBytes => write!(fmt, "invalid base64 in bytes"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for JsonError {}
+
+impl Ipld {
+    /// Converts into JSON with the given options.
+    pub fn into_json_with_options(self, options: &JsonOptions) -> Result<Value, JsonError> {
+        Ok(match self {
+            Ipld::Null => Value::Null,
+            Ipld::Bool(value) => Value::Bool(value),
+            Ipld::Integer(value) => {
+                if !options.allow_unsafe_integers && value.unsigned_abs() > MAX_SAFE_INTEGER as u128
+                {
+                    return Err(JsonError::IntegerOutOfRange(value));
+                }
+                if let Ok(value) = i64::try_from(value) {
+                    Value::Number(value.into())
+                } else if let Ok(value) = u64::try_from(value) {
+                    Value::Number(value.into())
+                } else {
+                    return Err(JsonError::IntegerOutOfRange(value));
+                }
+            }
+            Ipld::Float(value) => {
+                Value::Number(Number::from_f64(value).ok_or(JsonError::NonFiniteFloat(value))?)
+            }
+            Ipld::String(value) => Value::String(value),
+            Ipld::Bytes(_) if options.bytes_and_links == BytesAndLinks::Reject => {
+                return Err(JsonError::NotAllowed("bytes"));
+            }
+            Ipld::Bytes(bytes) => {
+                let mut inner = Map::new();
+                inner.insert(
+                    "bytes".to_string(),
+                    Value::String(multibase::encode_base64(&bytes)),
+                );
+                slash(Value::Object(inner))
+            }
+            Ipld::List(list) => Value::Array(
+                list.into_iter()
+                    .map(|ipld| ipld.into_json_with_options(options))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Ipld::Map(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, ipld)| Ok((key, ipld.into_json_with_options(options)?)))
+                    .collect::<Result<_, JsonError>>()?,
+            ),
+            Ipld::Link(_) if options.bytes_and_links == BytesAndLinks::Reject => {
+                return Err(JsonError::NotAllowed("links"));
+            }
+            Ipld::Link(cid) => slash(Value::String(cid.to_string())),
+        })
+    }
+}
+
+/// Converts into DAG-JSON shaped JSON with the default [`JsonOptions`].
+///
+/// Integers beyond [`MAX_SAFE_INTEGER`] are an error, use [`Ipld::into_json_with_options`] to
+/// allow them.
+impl TryFrom<Ipld> for Value {
+    type Error = JsonError;
+
+    fn try_from(ipld: Ipld) -> Result<Self, Self::Error> {
+        ipld.into_json_with_options(&JsonOptions::default())
+    }
+}
+
+/// Converts from DAG-JSON shaped JSON.
+///
+/// Objects whose only key is `"/"` are links if the value is a string and bytes if it is an
+/// object whose only key is `"bytes"`. Numbers without a fraction or an exponent that fit into
+/// 64 bits become `Integer`, all others `Float`.
+impl TryFrom<Value> for Ipld {
+    type Error = JsonError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Value::Null => Ipld::Null,
+            Value::Bool(value) => Ipld::Bool(value),
+            Value::Number(number) => {
+                if let Some(value) = number.as_i64() {
+                    Ipld::Integer(value.into())
+                } else if let Some(value) = number.as_u64() {
+                    Ipld::Integer(value.into())
+                } else {
+                    // Without `arbitrary_precision` every other number is a finite `f64`.
+                    Ipld::Float(number.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Value::String(value) => Ipld::String(value),
+            Value::Array(array) => Ipld::List(
+                array
+                    .into_iter()
+                    .map(Ipld::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(object) => {
+                if let Some(ipld) = special_object(&object)? {
+                    return Ok(ipld);
+                }
+                Ipld::Map(
+                    object
+                        .into_iter()
+                        .map(|(key, value)| Ok((key, Ipld::try_from(value)?)))
+                        .collect::<Result<_, JsonError>>()?,
+                )
+            }
+        })
+    }
+}
+
+/// Wraps a value as `{"/": value}`.
+fn slash(value: Value) -> Value {
+    let mut object = Map::new();
+    object.insert("/".to_string(), value);
+    Value::Object(object)
+}
+
+/// Returns the link or bytes if the object has one of their reserved shapes.
+fn special_object(object: &Map<String, Value>) -> Result<Option<Ipld>, JsonError> {
+    if object.len() != 1 {
+        return Ok(None);
+    }
+    match object.get("/") {
+        Some(Value::String(cid)) => {
+            let cid = Cid::from_str(cid).map_err(JsonError::InvalidLink)?;
+            Ok(Some(Ipld::Link(cid)))
+        }
+        Some(Value::Object(inner)) if inner.len() == 1 => match inner.get("bytes") {
+            Some(Value::String(bytes)) => multibase::decode_base64(bytes)
+                .map(|bytes| Some(Ipld::Bytes(bytes)))
+                .ok_or(JsonError::InvalidBytes),
+            _ => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
@@ -1,6 +1,63 @@
-use std::collections::BTreeMap;
+//! The IPLD data model.
+//!
+//! The `std` feature is enabled by default. Without it the crate is `no_std` and only needs
+//! `alloc`, the error types then don't implement `std::error::Error`.
+#![cfg_attr(not(feature = "std"), no_std)]
 
-#[derive(Debug, Clone, PartialEq)]
+extern crate alloc;
+
+use alloc::collections::{btree_map, BTreeMap};
+use alloc::string::{String, ToString};
+use alloc::vec::{self, Vec};
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::ops::{Index, IndexMut};
+
+mod borrowed;
+mod cid;
+mod convert;
+mod diagnostic;
+mod diff;
+mod index;
+mod iter;
+#[cfg(feature = "json")]
+mod json;
+mod macros;
+mod multibase;
+pub mod multihash;
+mod path;
+mod varint;
+
+pub use crate::borrowed::IpldRef;
+pub use crate::cid::{Cid, CidError, Version, DAG_PB};
+pub use crate::convert::ConversionError;
+pub use crate::diff::{diff, DiffKind};
+pub use crate::index::IpldIndex;
+pub use crate::iter::{Iter, Links, WalkControl};
+#[cfg(feature = "json")]
+pub use crate::json::{BytesAndLinks, JsonError, JsonOptions, MAX_SAFE_INTEGER};
+#[doc(hidden)]
+pub use crate::macros::__private;
+pub use crate::multibase::{decode_base64, encode_base64, IDENTITY as MULTIBASE_IDENTITY};
+pub use crate::multihash::Multihash;
+pub use crate::path::{Path, PathError};
+
+/// A value of the IPLD data model.
+///
+/// Values have a total order. Values of different variants are ordered by variant:
+/// `Null < Bool < Integer < Float < String < Bytes < List < Map < Link`. Within a variant,
+/// booleans, integers, strings and bytes compare as usual, lists and maps compare their elements
+/// and entries lexicographically and links compare their bytes. Floats are ordered by
+/// [`f64::total_cmp`], so `-0.0 < 0.0` and NaNs are equal to themselves, which DAG-CBOR never
+/// produces anyway. Equality and hashing follow the same rules, NaNs with different payloads
+/// are different values.
+///
+/// The order exists so that values can be sorted and used in sets and as map keys. It is an
+/// implementation detail of this crate, not an order the IPLD data model defines, and it may
+/// differ from the order of the encoded bytes.
+#[derive(Debug, Clone)]
 pub enum Ipld {
     Null,
     Bool(bool),
@@ -10,5 +67,725 @@ pub enum Ipld {
     Bytes(Vec<u8>),
     List(Vec<Ipld>),
     Map(BTreeMap<String, Ipld>),
-    Link(Vec<u8>),
+    /// A CID. Codecs add the multibase prefix they need when encoding it, e.g. DAG-CBOR the
+    /// 0x00 identity prefix in tag 42. The binary form of a CID becomes a link with
+    /// `Cid::try_from`, which checks that it is one.
+    Link(Cid),
+}
+
+impl Ipld {
+    /// Returns the boolean if this is a `Bool`.
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Ipld::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer if this is an `Integer`.
+    #[inline]
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            Ipld::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the float if this is a `Float`.
+    #[inline]
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Ipld::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string if this is a `String`.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Ipld::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes if this is `Bytes`.
+    #[inline]
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Ipld::Bytes(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if this is a `List`.
+    #[inline]
+    pub fn as_list(&self) -> Option<&[Ipld]> {
+        match self {
+            Ipld::List(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if this is a `Map`.
+    #[inline]
+    pub fn as_map(&self) -> Option<&BTreeMap<String, Ipld>> {
+        match self {
+            Ipld::Map(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the elements if this is a `List`.
+    ///
+    /// Unlike [`Ipld::iter`], this only visits the direct children.
+    #[inline]
+    pub fn items(&self) -> Option<impl Iterator<Item = &Ipld>> {
+        self.as_list().map(|list| list.iter())
+    }
+
+    /// Returns an iterator over the entries in key order if this is a `Map`.
+    #[inline]
+    pub fn entries(&self) -> Option<impl Iterator<Item = (&str, &Ipld)>> {
+        self.as_map()
+            .map(|map| map.iter().map(|(key, value)| (key.as_str(), value)))
+    }
+
+    /// Returns the CID if this is a `Link`.
+    #[inline]
+    pub fn as_link(&self) -> Option<&Cid> {
+        match self {
+            Ipld::Link(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Moves out the string if this is a `String`, otherwise gives the value back.
+    #[inline]
+    pub fn into_string(self) -> Result<String, Ipld> {
+        match self {
+            Ipld::String(value) => Ok(value),
+            other => Err(other),
+        }
+    }
+
+    /// Moves out the bytes if this is `Bytes`, otherwise gives the value back.
+    #[inline]
+    pub fn into_bytes(self) -> Result<Vec<u8>, Ipld> {
+        match self {
+            Ipld::Bytes(value) => Ok(value),
+            other => Err(other),
+        }
+    }
+
+    /// Moves out the elements if this is a `List`, otherwise gives the value back.
+    #[inline]
+    pub fn into_list(self) -> Result<Vec<Ipld>, Ipld> {
+        match self {
+            Ipld::List(value) => Ok(value),
+            other => Err(other),
+        }
+    }
+
+    /// Moves out the entries if this is a `Map`, otherwise gives the value back.
+    #[inline]
+    pub fn into_map(self) -> Result<BTreeMap<String, Ipld>, Ipld> {
+        match self {
+            Ipld::Map(value) => Ok(value),
+            other => Err(other),
+        }
+    }
+
+    /// Moves out the CID if this is a `Link`, otherwise gives the value back.
+    #[inline]
+    pub fn into_link(self) -> Result<Cid, Ipld> {
+        match self {
+            Ipld::Link(value) => Ok(value),
+            other => Err(other),
+        }
+    }
+
+    /// Returns an iterator that moves out the elements if this is a `List`, otherwise gives the
+    /// value back.
+    ///
+    /// `Ipld` itself isn't `IntoIterator`: lists and maps would yield different items, and an
+    /// iterator that is silently empty for scalars hides mistakes.
+    #[inline]
+    pub fn into_items(self) -> Result<vec::IntoIter<Ipld>, Ipld> {
+        self.into_list().map(IntoIterator::into_iter)
+    }
+
+    /// Returns an iterator that moves out the entries in key order if this is a `Map`, otherwise
+    /// gives the value back.
+    #[inline]
+    pub fn into_entries(self) -> Result<btree_map::IntoIter<String, Ipld>, Ipld> {
+        self.into_map().map(IntoIterator::into_iter)
+    }
+
+    /// Returns whether this is `Null`.
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Ipld::Null)
+    }
+
+    /// Returns whether this is a `Bool`.
+    #[inline]
+    pub fn is_bool(&self) -> bool {
+        matches!(self, Ipld::Bool(_))
+    }
+
+    /// Returns whether this is an `Integer`.
+    #[inline]
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Ipld::Integer(_))
+    }
+
+    /// Returns whether this is a `Float`.
+    #[inline]
+    pub fn is_float(&self) -> bool {
+        matches!(self, Ipld::Float(_))
+    }
+
+    /// Returns whether this is a `String`.
+    #[inline]
+    pub fn is_string(&self) -> bool {
+        matches!(self, Ipld::String(_))
+    }
+
+    /// Returns whether this is `Bytes`.
+    #[inline]
+    pub fn is_bytes(&self) -> bool {
+        matches!(self, Ipld::Bytes(_))
+    }
+
+    /// Returns whether this is a `List`.
+    #[inline]
+    pub fn is_list(&self) -> bool {
+        matches!(self, Ipld::List(_))
+    }
+
+    /// Returns whether this is a `Map`.
+    #[inline]
+    pub fn is_map(&self) -> bool {
+        matches!(self, Ipld::Map(_))
+    }
+
+    /// Returns whether this is a `Link`.
+    #[inline]
+    pub fn is_link(&self) -> bool {
+        matches!(self, Ipld::Link(_))
+    }
+
+    /// Returns the value of a map entry or a list element.
+    ///
+    /// Returns `None` if the key or index is absent, or if this is neither a map nor a list,
+    /// respectively.
+    pub fn get<I: IpldIndex>(&self, index: I) -> Option<&Ipld> {
+        index.index_into(self)
+    }
+
+    /// Like [`Ipld::get`], but returns a mutable reference.
+    pub fn get_mut<I: IpldIndex>(&mut self, index: I) -> Option<&mut Ipld> {
+        index.index_into_mut(self)
+    }
+
+    /// Moves the value out and leaves `Null` in its place, like `serde_json::Value::take`.
+    ///
+    /// Together with [`Ipld::get_mut`] this lifts a subtree out of a document without cloning
+    /// it.
+    pub fn take(&mut self) -> Ipld {
+        self.replace(Ipld::Null)
+    }
+
+    /// Puts `new` in place of the value and returns the old one.
+    pub fn replace(&mut self, new: Ipld) -> Ipld {
+        core::mem::replace(self, new)
+    }
+
+    /// Inserts the entries of the map `other` into this map, overwriting the ones with the same
+    /// keys.
+    ///
+    /// The merge is shallow: a value in `other` replaces the value in this map as a whole, even
+    /// if both are maps. Fails without changing anything if either value isn't a map.
+    pub fn merge(&mut self, other: Ipld) -> Result<(), ConversionError> {
+        let map = match self {
+            Ipld::Map(map) => map,
+            _ => return Err(ConversionError::wrong_variant("Map", self)),
+        };
+        map.extend(BTreeMap::try_from(other)?);
+        Ok(())
+    }
+
+    /// Walks a sequence of map keys and list indices, e.g. `ipld.get_in(&[&"items", &0])`.
+    ///
+    /// Returns `None` as soon as a segment can't be looked up.
+    pub fn get_in(&self, path: &[&dyn IpldIndex]) -> Option<&Ipld> {
+        path.iter()
+            .try_fold(self, |ipld, segment| segment.index_into(ipld))
+    }
+
+    /// Returns an element of a list, `None` if this isn't a list or the index is out of bounds.
+    pub fn get_index(&self, index: usize) -> Option<&Ipld> {
+        match self {
+            Ipld::List(list) => list.get(index),
+            _ => None,
+        }
+    }
+
+    /// Like [`Ipld::get_index`], but returns a mutable reference.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut Ipld> {
+        match self {
+            Ipld::List(list) => list.get_mut(index),
+            _ => None,
+        }
+    }
+
+    /// Resolves a `/` separated path like `foo/bar/0`.
+    ///
+    /// Segments walk into maps by key and into lists by numeric index. Segments are
+    /// percent-decoded like in [`Path`], so `a%2Fb` looks up the key `a/b`. Empty segments, e.g.
+    /// from leading or trailing slashes, are skipped; use [`Ipld::resolve_path`] to address
+    /// empty keys. Resolution stops at links: if segments remain, [`PathError::LinkEncountered`]
+    /// returns them together with the link, so that a caller that can load blocks may continue
+    /// there.
+    pub fn resolve(&self, path: &str) -> Result<&Ipld, PathError> {
+        self.resolve_path(&path::parse_skipping_empty(path)?)
+    }
+
+    /// Like [`Ipld::resolve`], but returns a mutable reference.
+    pub fn resolve_mut(&mut self, path: &str) -> Result<&mut Ipld, PathError> {
+        self.resolve_path_mut(&path::parse_skipping_empty(path)?)
+    }
+
+    /// Resolves a [`Path`], every segment is used as is, including empty ones.
+    ///
+    /// Fails in the same ways as [`Ipld::resolve`].
+    pub fn resolve_path(&self, path: &Path) -> Result<&Ipld, PathError> {
+        let segments = path.segments();
+        let mut ipld = self;
+        for (position, segment) in segments.iter().enumerate() {
+            ipld = match ipld {
+                Ipld::Map(map) => map.get(segment).ok_or_else(|| PathError::KeyNotFound {
+                    segment: segment.clone(),
+                })?,
+                Ipld::List(list) => {
+                    let index = segment
+                        .parse::<usize>()
+                        .map_err(|_| PathError::InvalidIndex {
+                            segment: segment.clone(),
+                        })?;
+                    list.get(index).ok_or(PathError::IndexOutOfBounds {
+                        index,
+                        len: list.len(),
+                    })?
+                }
+                Ipld::Link(link) => {
+                    return Err(PathError::LinkEncountered {
+                        remaining_path: Path::from_segments(&segments[position..]),
+                        link: link.clone(),
+                    });
+                }
+                _ => {
+                    return Err(PathError::NotTraversable {
+                        segment: segment.clone(),
+                    })
+                }
+            };
+        }
+        Ok(ipld)
+    }
+
+    /// Like [`Ipld::resolve_path`], but returns a mutable reference.
+    pub fn resolve_path_mut(&mut self, path: &Path) -> Result<&mut Ipld, PathError> {
+        let segments = path.segments();
+        let mut ipld = self;
+        for (position, segment) in segments.iter().enumerate() {
+            ipld = match ipld {
+                Ipld::Map(map) => map.get_mut(segment).ok_or_else(|| PathError::KeyNotFound {
+                    segment: segment.clone(),
+                })?,
+                Ipld::List(list) => {
+                    let index = segment
+                        .parse::<usize>()
+                        .map_err(|_| PathError::InvalidIndex {
+                            segment: segment.clone(),
+                        })?;
+                    let len = list.len();
+                    list.get_mut(index)
+                        .ok_or(PathError::IndexOutOfBounds { index, len })?
+                }
+                Ipld::Link(link) => {
+                    return Err(PathError::LinkEncountered {
+                        remaining_path: Path::from_segments(&segments[position..]),
+                        link: link.clone(),
+                    });
+                }
+                _ => {
+                    return Err(PathError::NotTraversable {
+                        segment: segment.clone(),
+                    })
+                }
+            };
+        }
+        Ok(ipld)
+    }
+
+    /// Returns an iterator over this value and everything nested in it, together with their
+    /// paths.
+    ///
+    /// Values are visited depth-first in document order, every list or map comes right before
+    /// its contents. Map entries are visited in the order DAG-CBOR encodes them, shorter keys
+    /// first and keys of the same length bytewise. The first item is this value with
+    /// the empty path. No recursion is involved, so arbitrarily deep values can be walked, though
+    /// every item comes with its own copy of the path.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(self)
+    }
+
+    /// Calls `f` on this value and everything nested in it, in the same order as [`Ipld::iter`].
+    ///
+    /// Unlike [`Ipld::iter`], no paths are built, so this is the cheaper way to visit every
+    /// value, e.g. to gather statistics.
+    pub fn walk<F>(&self, f: &mut F)
+    where
+        F: FnMut(&Ipld),
+    {
+        iter::walk(self, f)
+    }
+
+    /// Calls `f` on this value and everything nested in it, in the same order as [`Ipld::iter`].
+    ///
+    /// The callback may modify or replace the value it gets, its return value decides whether
+    /// the walk descends into the value as it is afterwards. Replacing a map with a scalar and
+    /// returning [`WalkControl::Continue`] simply has no children to walk.
+    pub fn walk_mut<F>(&mut self, f: F)
+    where
+        F: FnMut(&Path, &mut Ipld) -> WalkControl,
+    {
+        iter::walk_mut(self, f)
+    }
+
+    /// Returns whether both values have the same canonical DAG-CBOR encoding, without encoding
+    /// them.
+    ///
+    /// Values that have no encoding at all are never canonically equal, not even to themselves.
+    /// DAG-CBOR doesn't allow NaN and infinite floats or integers outside the 64-bit range, from
+    /// `i64::MIN` to `u64::MAX`, `==` compares them like all other values. Apart from that this is
+    /// the same as `==`, see its documentation. Use this method where byte equality is what
+    /// matters, e.g. when deduplicating blocks by value.
+    pub fn canonical_eq(&self, other: &Ipld) -> bool {
+        if self != other {
+            return false;
+        }
+        let mut encodable = true;
+        self.walk(&mut |ipld| match ipld {
+            Ipld::Integer(value) => {
+                encodable &= i128::from(i64::MIN) <= *value && *value <= i128::from(u64::MAX)
+            }
+            Ipld::Float(value) => encodable &= value.is_finite(),
+            _ => {}
+        });
+        encodable
+    }
+
+    /// Returns an iterator over every link in this value.
+    ///
+    /// Lists and maps are descended lazily and depth-first, the links are returned in the order
+    /// they appear in the DAG-CBOR encoding, like with [`Ipld::iter`]. Links that appear more
+    /// than once are returned each time.
+    pub fn links(&self) -> Links<'_> {
+        Links::new(self)
+    }
+}
+
+impl Ipld {
+    /// The position of the variant in the order of variants.
+    fn rank(&self) -> u8 {
+        match self {
+            Ipld::Null => 0,
+            Ipld::Bool(_) => 1,
+            Ipld::Integer(_) => 2,
+            Ipld::Float(_) => 3,
+            Ipld::String(_) => 4,
+            Ipld::Bytes(_) => 5,
+            Ipld::List(_) => 6,
+            Ipld::Map(_) => 7,
+            Ipld::Link(_) => 8,
+        }
+    }
+}
+
+/// Equality agrees with the [`Ord`] implementation. Floats are compared by their bits, so `0.0`
+/// and `-0.0` differ, just like their DAG-CBOR encodings, and a NaN equals a NaN with the same
+/// payload. Values that can be encoded are equal exactly when their encodings are, see
+/// [`Ipld::canonical_eq`] for the ones that can't.
+impl PartialEq for Ipld {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ipld {}
+
+impl PartialOrd for Ipld {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ipld {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Ipld::Null, Ipld::Null) => Ordering::Equal,
+            (Ipld::Bool(a), Ipld::Bool(b)) => a.cmp(b),
+            (Ipld::Integer(a), Ipld::Integer(b)) => a.cmp(b),
+            (Ipld::Float(a), Ipld::Float(b)) => a.total_cmp(b),
+            (Ipld::String(a), Ipld::String(b)) => a.cmp(b),
+            (Ipld::Bytes(a), Ipld::Bytes(b)) => a.cmp(b),
+            (Ipld::List(a), Ipld::List(b)) => a.cmp(b),
+            (Ipld::Map(a), Ipld::Map(b)) => a.cmp(b),
+            (Ipld::Link(a), Ipld::Link(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// Hashes the variant and its contents, consistent with equality. Floats are hashed by their
+/// bit pattern, map entries in key order.
+impl Hash for Ipld {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            Ipld::Null => {}
+            Ipld::Bool(value) => value.hash(state),
+            Ipld::Integer(value) => value.hash(state),
+            Ipld::Float(value) => value.to_bits().hash(state),
+            Ipld::String(value) => value.hash(state),
+            Ipld::Bytes(value) => value.hash(state),
+            Ipld::List(value) => value.hash(state),
+            // A `BTreeMap` iterates and hashes in key order.
+            Ipld::Map(value) => value.hash(state),
+            Ipld::Link(value) => Hash::hash(value, state),
+        }
+    }
+}
+
+impl PartialEq<bool> for Ipld {
+    fn eq(&self, other: &bool) -> bool {
+        self.as_bool() == Some(*other)
+    }
+}
+
+macro_rules! eq_integer {
+    ($($ty:ty),*) => {
+        $(
+            /// Only an `Integer` is equal to an integer, never a `Float` of the same value.
+            impl PartialEq<$ty> for Ipld {
+                fn eq(&self, other: &$ty) -> bool {
+                    self.as_integer() == Some(*other as i128)
+                }
+            }
+        )*
+    };
+}
+
+eq_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+/// Compares like two `Float`s do, so NaN equals NaN and `0.0` doesn't equal `-0.0`.
+impl PartialEq<f64> for Ipld {
+    fn eq(&self, other: &f64) -> bool {
+        matches!(self, Ipld::Float(value) if value.total_cmp(other) == Ordering::Equal)
+    }
+}
+
+impl PartialEq<str> for Ipld {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == Some(other)
+    }
+}
+
+impl PartialEq<&str> for Ipld {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == Some(*other)
+    }
+}
+
+impl PartialEq<String> for Ipld {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == Some(other.as_str())
+    }
+}
+
+impl PartialEq<Cid> for Ipld {
+    fn eq(&self, other: &Cid) -> bool {
+        self.as_link() == Some(other)
+    }
+}
+
+impl From<bool> for Ipld {
+    fn from(value: bool) -> Self {
+        Ipld::Bool(value)
+    }
+}
+
+macro_rules! from_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Ipld {
+                fn from(value: $ty) -> Self {
+                    Ipld::Integer(value as i128)
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+impl From<f32> for Ipld {
+    fn from(value: f32) -> Self {
+        Ipld::Float(f64::from(value))
+    }
+}
+
+impl From<f64> for Ipld {
+    fn from(value: f64) -> Self {
+        Ipld::Float(value)
+    }
+}
+
+impl From<String> for Ipld {
+    fn from(value: String) -> Self {
+        Ipld::String(value)
+    }
+}
+
+impl From<&str> for Ipld {
+    fn from(value: &str) -> Self {
+        Ipld::String(value.to_string())
+    }
+}
+
+/// Bytes become `Ipld::Bytes`, convert a [`Cid`] for links.
+impl From<Vec<u8>> for Ipld {
+    fn from(value: Vec<u8>) -> Self {
+        Ipld::Bytes(value)
+    }
+}
+
+impl From<&[u8]> for Ipld {
+    fn from(value: &[u8]) -> Self {
+        Ipld::Bytes(value.to_vec())
+    }
+}
+
+impl From<Cid> for Ipld {
+    fn from(cid: Cid) -> Self {
+        Ipld::Link(cid)
+    }
+}
+
+/// `None` becomes `Ipld::Null`.
+impl<T: Into<Ipld>> From<Option<T>> for Ipld {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Ipld::Null, Into::into)
+    }
+}
+
+impl From<Vec<Ipld>> for Ipld {
+    fn from(value: Vec<Ipld>) -> Self {
+        Ipld::List(value)
+    }
+}
+
+impl From<BTreeMap<String, Ipld>> for Ipld {
+    fn from(value: BTreeMap<String, Ipld>) -> Self {
+        Ipld::Map(value)
+    }
+}
+
+/// Collects into a `List`. A generic `From<Vec<T>>` would clash with `Vec<u8>` becoming
+/// `Bytes`, so collect a `Vec<String>` or similar with `vec.into_iter().collect()` instead.
+impl<T: Into<Ipld>> FromIterator<T> for Ipld {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Ipld::List(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Collects into a `Map`. Like with `BTreeMap`, the last value wins if a key repeats.
+impl<K: Into<String>, V: Into<Ipld>> FromIterator<(K, V)> for Ipld {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Ipld::Map(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+static NULL: Ipld = Ipld::Null;
+
+/// Indexes into a map.
+///
+/// Like `serde_json::Value`, this never panics: if this isn't a map or the key is absent, a
+/// reference to `Ipld::Null` is returned. This way chains like `node["a"]["b"]` degrade
+/// gracefully. Use [`Ipld::get`] to tell a missing key apart from a `Null` value.
+impl Index<&str> for Ipld {
+    type Output = Ipld;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// Indexes into a list.
+///
+/// If this isn't a list or the index is out of bounds, a reference to `Ipld::Null` is returned
+/// instead of panicking. Use [`Ipld::get_index`] to tell those cases apart from a `Null` value.
+impl Index<usize> for Ipld {
+    type Output = Ipld;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+/// Mutably indexes into a map, inserting `Ipld::Null` if the key is absent.
+///
+/// Like `serde_json::Value`, a `Null` is turned into an empty map first, so
+/// `node["a"]["b"] = value` builds nested maps as needed.
+///
+/// # Panics
+///
+/// Panics if this is neither a map nor `Null`.
+impl IndexMut<&str> for Ipld {
+    fn index_mut(&mut self, key: &str) -> &mut Self::Output {
+        if let Ipld::Null = self {
+            *self = Ipld::Map(BTreeMap::new());
+        }
+        match self {
+            Ipld::Map(map) => map.entry(key.to_string()).or_insert(Ipld::Null),
+            _ => panic!("cannot access key {:?} in a non-map value", key),
+        }
+    }
+}
+
+/// Mutably indexes into a list.
+///
+/// # Panics
+///
+/// Panics if this isn't a list or the index is out of bounds, like `serde_json::Value`.
+impl IndexMut<usize> for Ipld {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match self {
+            Ipld::List(list) => {
+                let len = list.len();
+                list.get_mut(index).unwrap_or_else(|| {
+                    panic!("cannot access index {} of a list of length {}", index, len)
+                })
+            }
+            _ => panic!("cannot access index {} in a non-list value", index),
+        }
+    }
 }
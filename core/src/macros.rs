@@ -0,0 +1,110 @@
+/// Builds an `Ipld` value with a JSON-like syntax.
+///
+/// `null`, `true` and `false` are keywords, `[...]` builds a `List` and `{...}` a `Map` whose
+/// keys are string literals or parenthesized expressions. `Bytes(bytes)` builds `Bytes` from
+/// anything that converts into a `Vec<u8>`, e.g. a byte string literal. `Link(cid)` builds a
+/// link from a `Cid` or a `&Cid`. Anything else is an expression that is converted with
+/// `Ipld::from`, e.g. integers become `Integer`, floats `Float` and strings `String`.
+///
+/// ```
+/// use std::str::FromStr;
+///
+/// use ipld_core::{ipld, Cid, Ipld};
+///
+/// let cid = Cid::from_str("bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua").unwrap();
+/// let node = ipld!({
+///     "name": "x",
+///     "items": [1, 2, 3],
+///     "data": Bytes(b"\x01\x02"),
+///     "link": Link(&cid),
+/// });
+/// assert_eq!(node["items"][1], Ipld::Integer(2));
+/// ```
+#[macro_export]
+macro_rules! ipld {
+    (null) => {
+        $crate::Ipld::Null
+    };
+    (true) => {
+        $crate::Ipld::Bool(true)
+    };
+    (false) => {
+        $crate::Ipld::Bool(false)
+    };
+    (Bytes($bytes:expr)) => {
+        $crate::Ipld::Bytes(::core::convert::Into::<$crate::__private::Vec<u8>>::into($bytes))
+    };
+    (Link($link:expr)) => {
+        $crate::Ipld::Link($crate::__private::IntoLink::into_link($link))
+    };
+    ([ $($tt:tt)* ]) => {
+        $crate::Ipld::List($crate::ipld!(@list [] [] $($tt)*))
+    };
+    ({ $($tt:tt)* }) => {{
+        #[allow(unused_mut)]
+        let mut map = $crate::__private::BTreeMap::new();
+        $crate::ipld!(@map map $($tt)*);
+        $crate::Ipld::Map(map)
+    }};
+    ($other:expr) => {
+        $crate::Ipld::from($other)
+    };
+
+    // Collects the tokens of the current list element until the next comma.
+    (@list [$($done:expr,)*] [$($current:tt)+] , $($rest:tt)*) => {
+        $crate::ipld!(@list [$($done,)* $crate::ipld!($($current)+),] [] $($rest)*)
+    };
+    (@list [$($done:expr,)*] [$($current:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::ipld!(@list [$($done,)*] [$($current)* $next] $($rest)*)
+    };
+    (@list [$($done:expr,)*] []) => {
+        $crate::__private::vec![$($done,)*]
+    };
+    (@list [$($done:expr,)*] [$($current:tt)+]) => {
+        $crate::__private::vec![$($done,)* $crate::ipld!($($current)+)]
+    };
+
+    // Collects the tokens of the current map value until the next comma.
+    (@map $map:ident ($key:expr) [$($current:tt)+] , $($rest:tt)*) => {
+        $map.insert($crate::__private::String::from($key), $crate::ipld!($($current)+));
+        $crate::ipld!(@map $map $($rest)*);
+    };
+    (@map $map:ident ($key:expr) [$($current:tt)+]) => {
+        $map.insert($crate::__private::String::from($key), $crate::ipld!($($current)+));
+    };
+    (@map $map:ident ($key:expr) [$($current:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::ipld!(@map $map ($key) [$($current)* $next] $($rest)*)
+    };
+    (@map $map:ident $key:tt : $($rest:tt)*) => {
+        $crate::ipld!(@map $map ($key) [] $($rest)*)
+    };
+    (@map $map:ident) => {};
+}
+
+/// Not public API, used by the `ipld!` macro.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::collections::BTreeMap;
+    pub use alloc::string::String;
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+
+    use crate::Cid;
+
+    /// What `Link(...)` accepts.
+    pub trait IntoLink {
+        fn into_link(self) -> Cid;
+    }
+
+    impl IntoLink for Cid {
+        fn into_link(self) -> Cid {
+            self
+        }
+    }
+
+    impl IntoLink for &Cid {
+        fn into_link(self) -> Cid {
+            self.clone()
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! The multibase encodings used for the string form of CIDs.
+//!
+//! Only base16, base32 without padding and base58btc are supported, plus the base64 that DAG-JSON
+//! uses for bytes. They are small enough to be implemented here instead of pulling in a
+//! dependency. Base16 and base32 are encoded in lowercase, but decoded in either case.
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The multibase prefix of the identity encoding, which leaves binary data as it is. DAG-CBOR
+/// puts it in front of every CID, see [`Cid::link_bytes`](crate::Cid::link_bytes).
+pub const IDENTITY: u8 = 0x00;
+/// The multibase prefix of base16.
+pub(crate) const BASE16: char = 'f';
+/// The multibase prefix of base32.
+pub(crate) const BASE32: char = 'b';
+/// The multibase prefix of base58btc.
+pub(crate) const BASE58BTC: char = 'z';
+
+/// Decodes base16, in either case.
+pub(crate) fn decode_base16(string: &str) -> Option<Vec<u8>> {
+    string
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let high = char::from(pair[0]).to_digit(16)?;
+            // The last chunk is a single digit if the length is odd.
+            let low = char::from(*pair.get(1)?).to_digit(16)?;
+            Some((high << 4 | low) as u8)
+        })
+        .collect()
+}
+
+pub(crate) fn encode_base32(bytes: &[u8]) -> String {
+    let mut string = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            string.push(BASE32_ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char);
+        }
+    }
+    if bits > 0 {
+        string.push(BASE32_ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char);
+    }
+    string
+}
+
+/// Decodes base32 in either case.
+pub(crate) fn decode_base32(string: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(string.len() * 5 / 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in string.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|digit| *digit == byte.to_ascii_lowercase())?;
+        buffer = (buffer << 5) | value as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    // Left over bits are padding and need to be zero, a whole character can't be padding.
+    if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(bytes)
+}
+
+pub(crate) fn encode_base58(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    // The digits in base 58, least significant first.
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for byte in &bytes[zeros..] {
+        let mut carry = u32::from(*byte);
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut string = "1".repeat(zeros);
+    string.extend(
+        digits
+            .iter()
+            .rev()
+            .map(|digit| BASE58_ALPHABET[usize::from(*digit)] as char),
+    );
+    string
+}
+
+pub(crate) fn decode_base58(string: &str) -> Option<Vec<u8>> {
+    let zeros = string.bytes().take_while(|byte| *byte == b'1').count();
+    // The bytes in base 256, least significant first.
+    let mut bytes: Vec<u8> = Vec::with_capacity(string.len());
+    for byte in string.bytes().skip(zeros) {
+        let mut carry = BASE58_ALPHABET.iter().position(|digit| *digit == byte)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    bytes.resize(bytes.len() + zeros, 0);
+    bytes.reverse();
+    Some(bytes)
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes standard base64 without padding, which DAG-JSON uses for bytes.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    let mut string = String::with_capacity((bytes.len() * 4).div_ceil(3));
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+        while bits >= 6 {
+            bits -= 6;
+            string.push(BASE64_ALPHABET[usize::from((buffer >> bits) & 0x3f)] as char);
+        }
+    }
+    if bits > 0 {
+        string.push(BASE64_ALPHABET[usize::from((buffer << (6 - bits)) & 0x3f)] as char);
+    }
+    string
+}
+
+/// Decodes the bytes of DAG-JSON, standard base64 with or without padding. The base64url
+/// alphabet is accepted as well, as some encoders use it.
+pub fn decode_base64(string: &str) -> Option<Vec<u8>> {
+    let string = string.trim_end_matches('=');
+    let mut bytes = Vec::with_capacity(string.len() * 3 / 4);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in string.bytes() {
+        let value = match byte {
+            b'-' => 62,
+            b'_' => 63,
+            _ => BASE64_ALPHABET.iter().position(|digit| *digit == byte)? as u16,
+        };
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    if bits >= 6 || buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(bytes)
+}
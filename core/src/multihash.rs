@@ -0,0 +1,108 @@
+//! Self-describing hashes.
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use sha2::Digest;
+
+use crate::cid::CidError;
+use crate::varint;
+
+/// The multihash code of the identity hash, the digest is the data itself.
+pub const IDENTITY: u64 = 0x00;
+/// The multihash code of SHA2-256.
+pub const SHA2_256: u64 = 0x12;
+/// The multihash code of SHA2-512.
+pub const SHA2_512: u64 = 0x13;
+
+/// The hash functions that multihashes can be computed with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Code {
+    /// The identity hash, only useful for very small data.
+    Identity,
+    /// SHA2-256, what IPFS uses by default.
+    Sha2_256,
+    /// SHA2-512.
+    Sha2_512,
+}
+
+impl Code {
+    /// Returns the multihash code.
+    pub fn code(self) -> u64 {
+        match self {
+            Code::Identity => IDENTITY,
+            Code::Sha2_256 => SHA2_256,
+            Code::Sha2_512 => SHA2_512,
+        }
+    }
+
+    /// Returns the hash function with the given multihash code, if it is supported.
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            IDENTITY => Some(Code::Identity),
+            SHA2_256 => Some(Code::Sha2_256),
+            SHA2_512 => Some(Code::Sha2_512),
+            _ => None,
+        }
+    }
+
+    /// Hashes `data`.
+    pub fn digest(self, data: &[u8]) -> Multihash {
+        let digest = match self {
+            Code::Identity => data.to_vec(),
+            Code::Sha2_256 => sha2::Sha256::digest(data).to_vec(),
+            Code::Sha2_512 => sha2::Sha512::digest(data).to_vec(),
+        };
+        Multihash::new(self.code(), digest)
+    }
+}
+
+/// A hash digest together with the code of the hash function that produced it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Multihash {
+    code: u64,
+    digest: Vec<u8>,
+}
+
+impl Multihash {
+    /// Creates a multihash from the code of a hash function and a digest.
+    pub fn new(code: u64, digest: Vec<u8>) -> Self {
+        Self { code, digest }
+    }
+
+    /// Returns the code of the hash function.
+    pub fn code(&self) -> u64 {
+        self.code
+    }
+
+    /// Returns the digest.
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Returns the binary representation: the code, the length of the digest and the digest.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.digest.len() + 2);
+        varint::write(self.code, &mut bytes);
+        varint::write(self.digest.len() as u64, &mut bytes);
+        bytes.extend_from_slice(&self.digest);
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for Multihash {
+    type Error = CidError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let (code, code_len) = varint::read(bytes).ok_or(CidError::InvalidVarint)?;
+        let bytes = &bytes[code_len..];
+        let (len, len_len) = varint::read(bytes).ok_or(CidError::InvalidVarint)?;
+        let digest = &bytes[len_len..];
+        if digest.len() as u64 != len {
+            return Err(CidError::InvalidDigestLength {
+                expected: len,
+                actual: digest.len(),
+            });
+        }
+        Ok(Self::new(code, digest.to_vec()))
+    }
+}
@@ -0,0 +1,185 @@
+//! Resolving `/` separated paths.
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::Cid;
+
+/// A path into an `Ipld` value, a list of map keys and list indices.
+///
+/// The string form puts a `/` in front of every segment, e.g. `/foo/bar/0`. Within a segment
+/// `%` and `/` are percent-encoded as `%25` and `%2F`, so that any map key, including ones
+/// that contain slashes or are empty, can be addressed. The empty path is the empty string.
+///
+/// When parsing, the leading `/` is optional, and any percent-encoded UTF-8 is decoded, not
+/// only `%25` and `%2F`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Path {
+    segments: Vec<String>,
+}
+
+impl Path {
+    /// Creates a path from unescaped segments.
+    pub fn from_segments<I, S>(segments: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            segments: segments.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns the unescaped segments.
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// Appends an unescaped segment.
+    pub fn push<S: Into<String>>(&mut self, segment: S) {
+        self.segments.push(segment.into());
+    }
+
+    /// Shortens the path to its first `len` segments.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.segments.truncate(len);
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            fmt.write_str("/")?;
+            for c in segment.chars() {
+                match c {
+                    '%' => fmt.write_str("%25")?,
+                    '/' => fmt.write_str("%2F")?,
+                    c => write!(fmt, "{}", c)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Path {
+    type Err = PathError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if string.is_empty() {
+            return Ok(Self::default());
+        }
+        let string = string.strip_prefix('/').unwrap_or(string);
+        let segments = string.split('/').map(unescape).collect::<Result<_, _>>()?;
+        Ok(Self { segments })
+    }
+}
+
+/// Parses a path the way [`Ipld::resolve`] does, empty segments are skipped.
+///
+/// [`Ipld::resolve`]: crate::Ipld::resolve
+pub(crate) fn parse_skipping_empty(path: &str) -> Result<Path, PathError> {
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(unescape)
+        .collect::<Result<_, _>>()?;
+    Ok(Path { segments })
+}
+
+/// Decodes the percent-encoding of a single segment.
+fn unescape(segment: &str) -> Result<String, PathError> {
+    if !segment.contains('%') {
+        return Ok(segment.to_string());
+    }
+    let invalid = || PathError::InvalidEscape {
+        segment: segment.to_string(),
+    };
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            // `from_str_radix` would accept a leading `+` as well.
+            let hex = tail
+                .get(..2)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| core::str::from_utf8(hex).ok())
+                .ok_or_else(invalid)?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Why a path couldn't be resolved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathError {
+    /// A map doesn't contain the key.
+    KeyNotFound {
+        /// The segment that failed.
+        segment: String,
+    },
+    /// A segment isn't a valid index into a list.
+    InvalidIndex {
+        /// The segment that failed.
+        segment: String,
+    },
+    /// An index is beyond the end of a list.
+    IndexOutOfBounds {
+        /// The index.
+        index: usize,
+        /// The length of the list.
+        len: usize,
+    },
+    /// A segment tries to descend into something that is neither a list, a map nor a link.
+    NotTraversable {
+        /// The segment that failed.
+        segment: String,
+    },
+    /// The path continues past a link. The remaining path can be resolved in the linked block.
+    LinkEncountered {
+        /// The rest of the path, starting with the segment after the link.
+        remaining_path: Path,
+        /// The link.
+        link: Cid,
+    },
+    /// A segment contains a `%` that isn't followed by two hex digits, or decodes to invalid
+    /// UTF-8.
+    InvalidEscape {
+        /// The segment that failed.
+        segment: String,
+    },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::KeyNotFound { segment } => write!(fmt, "key {:?} not found", segment),
+            PathError::InvalidIndex { segment } => {
+                write!(fmt, "{:?} is not a valid list index", segment)
+            }
+            PathError::IndexOutOfBounds { index, len } => write!(
+                fmt,
+                "index {} is out of bounds for a list of length {}",
+                index, len
+            ),
+            PathError::NotTraversable { segment } => {
+                write!(fmt, "can't descend into a scalar with {:?}", segment)
+            }
+            PathError::LinkEncountered { remaining_path, .. } => {
+                write!(fmt, "encountered a link, \"{}\" remains", remaining_path)
+            }
+            PathError::InvalidEscape { segment } => {
+                write!(fmt, "{:?} contains an invalid escape", segment)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PathError {}
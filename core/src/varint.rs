@@ -0,0 +1,63 @@
+//! Unsigned varints as used by the multiformats.
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// A varint is at most nine bytes long, so that it encodes at most 63 bits.
+const MAX_LEN: usize = 9;
+
+/// Appends the varint encoding of `value` to `out`.
+pub(crate) fn write(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Returns the length of the varint encoding of `value`.
+pub(crate) fn len(value: u64) -> usize {
+    // Every byte holds seven bits, zero still needs one byte.
+    (64 - value.leading_zeros() as usize).div_ceil(7).max(1)
+}
+
+/// Reads a varint from the start of `bytes`, returns it with the number of bytes it occupied.
+///
+/// Returns `None` if the bytes end within the varint, if it is longer than nine bytes or if it
+/// isn't minimally encoded.
+pub(crate) fn read(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0;
+    for (index, byte) in bytes.iter().take(MAX_LEN).enumerate() {
+        value |= u64::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            // A trailing zero byte means the varint could have been shorter.
+            if *byte == 0 && index > 0 {
+                return None;
+            }
+            return Some((value, index + 1));
+        }
+    }
+    None
+}
+
+/// Compares two values by their varint encodings.
+///
+/// That isn't the numeric order, e.g. 0x81 is encoded as `0x81 0x01` and sorts after 0x100,
+/// which is encoded as `0x80 0x02`. No varint is a prefix of another one, so values that follow
+/// varints in a binary form can be compared after them.
+pub(crate) fn cmp(a: u64, b: u64) -> Ordering {
+    /// Encodes without allocating, a `u64` needs at most ten bytes.
+    fn encode(mut value: u64) -> ([u8; 10], usize) {
+        let mut bytes = [0; 10];
+        let mut len = 0;
+        while value >= 0x80 {
+            bytes[len] = value as u8 | 0x80;
+            value >>= 7;
+            len += 1;
+        }
+        bytes[len] = value as u8;
+        (bytes, len + 1)
+    }
+    let (a, a_len) = encode(a);
+    let (b, b_len) = encode(b);
+    a[..a_len].cmp(&b[..b_len])
+}
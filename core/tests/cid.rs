@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use ipld_core::multihash::Code;
+use ipld_core::{multihash, Cid, CidError, Ipld, Multihash, Version, DAG_PB, MULTIBASE_IDENTITY};
+
+/// `bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi`
+const CID_V1_DAG_PB: [u8; 36] = [
+    0x01, 0x70, 0x12, 0x20, 0xc3, 0xc4, 0x73, 0x3e, 0xc8, 0xaf, 0xfd, 0x06, 0xcf, 0x9e, 0x9f, 0xf5,
+    0x0f, 0xfc, 0x6b, 0xcd, 0x2e, 0xc8, 0x5a, 0x61, 0x70, 0x00, 0x4b, 0xb7, 0x09, 0x66, 0x9c, 0x31,
+    0xde, 0x94, 0x39, 0x1a,
+];
+
+#[test]
+fn parse_cid_v1() {
+    let cid = Cid::try_from(&CID_V1_DAG_PB[..]).unwrap();
+    assert_eq!(cid.version(), Version::V1);
+    assert_eq!(cid.codec(), DAG_PB);
+    assert_eq!(cid.hash().code(), multihash::SHA2_256);
+    assert_eq!(cid.hash().digest(), &CID_V1_DAG_PB[4..]);
+    assert_eq!(cid.to_bytes(), CID_V1_DAG_PB.to_vec());
+}
+
+#[test]
+fn parse_cid_v1_dag_cbor() {
+    // The CID of the DAG-CBOR encoded empty map.
+    let cid = Cid::from_str("bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua").unwrap();
+    assert_eq!(cid.version(), Version::V1);
+    assert_eq!(cid.codec(), 0x71);
+    assert_eq!(cid.hash().code(), multihash::SHA2_256);
+    assert_eq!(cid.hash().digest().len(), 32);
+    assert_eq!(cid.to_bytes()[..4], [0x01, 0x71, 0x12, 0x20]);
+
+    // Truncated after the version, after the codec and within the hash header.
+    let bytes = cid.to_bytes();
+    for len in 1..4 {
+        assert!(Cid::try_from(&bytes[..len]).is_err(), "{}", len);
+    }
+}
+
+#[test]
+fn parse_cid_v0() {
+    let cid = Cid::try_from(&CID_V1_DAG_PB[2..]).unwrap();
+    assert_eq!(cid.version(), Version::V0);
+    assert_eq!(cid.codec(), DAG_PB);
+    assert_eq!(cid.to_bytes(), CID_V1_DAG_PB[2..].to_vec());
+}
+
+#[test]
+fn parse_invalid() {
+    assert_eq!(Cid::try_from(vec![]), Err(CidError::InvalidVarint));
+    assert_eq!(
+        Cid::try_from(vec![0x02, 0x70]),
+        Err(CidError::UnknownVersion(2))
+    );
+    assert_eq!(
+        Cid::try_from(&CID_V1_DAG_PB[..35]),
+        Err(CidError::InvalidDigestLength {
+            expected: 32,
+            actual: 31
+        })
+    );
+    // Varints must not have trailing zero bytes.
+    assert_eq!(
+        Cid::try_from(vec![0x81, 0x00, 0x70]),
+        Err(CidError::InvalidVarint)
+    );
+}
+
+#[test]
+fn new_cid_v0() {
+    let hash = Multihash::new(multihash::SHA2_256, CID_V1_DAG_PB[4..].to_vec());
+    assert_eq!(
+        Cid::new_v0(hash).unwrap().to_bytes(),
+        CID_V1_DAG_PB[2..].to_vec()
+    );
+    let hash = Multihash::new(0x13, vec![0; 64]);
+    assert_eq!(Cid::new_v0(hash), Err(CidError::InvalidCidV0));
+}
+
+#[test]
+fn cid_string_roundtrip() {
+    let fixtures = [
+        (
+            "QmbWqxBEKC3P8tqsKc98xmWNzrzDtRLMiMPL8wBuTGsMnR",
+            &CID_V1_DAG_PB[2..],
+        ),
+        (
+            "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            &CID_V1_DAG_PB[..],
+        ),
+    ];
+    for (string, bytes) in fixtures.iter() {
+        let cid = Cid::from_str(string).unwrap();
+        assert_eq!(cid.to_bytes(), bytes.to_vec());
+        assert_eq!(&cid.to_string(), string);
+        assert_eq!(Cid::from_str(&cid.to_string()).unwrap(), cid);
+        assert_eq!(format!("{:?}", cid), format!("Cid({})", string));
+    }
+}
+
+#[test]
+fn parse_cid_string_other_bases() {
+    let cid = Cid::try_from(&CID_V1_DAG_PB[..]).unwrap();
+    assert_eq!(
+        Cid::from_str("zdj7Wic6KcJAfWz1c9o4M6kq9Lwd5BfbxkVafnrojaaGiSFxM").unwrap(),
+        cid
+    );
+    assert_eq!(
+        Cid::from_str("BAFYBEIGDYRZT5SFP7UDM7HU76UH7Y26NF3EFUYLQABF3OCLGTQY55FBZDI").unwrap(),
+        cid
+    );
+    let hex: String = CID_V1_DAG_PB
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    assert_eq!(Cid::from_str(&format!("f{}", hex)).unwrap(), cid);
+    assert_eq!(
+        Cid::from_str(&format!("F{}", hex.to_uppercase())).unwrap(),
+        cid
+    );
+}
+
+#[test]
+fn parse_invalid_cid_string() {
+    assert_eq!(Cid::from_str(""), Err(CidError::InvalidBaseEncoding));
+    assert_eq!(
+        Cid::from_str("mAXASIA"),
+        Err(CidError::UnknownMultibase('m'))
+    );
+    // Characters outside the alphabet.
+    assert_eq!(Cid::from_str("bafy0"), Err(CidError::InvalidBaseEncoding));
+    assert_eq!(Cid::from_str("zdj7W0"), Err(CidError::InvalidBaseEncoding));
+    assert_eq!(Cid::from_str("f0170xx"), Err(CidError::InvalidBaseEncoding));
+    assert_eq!(Cid::from_str("f017"), Err(CidError::InvalidBaseEncoding));
+    // A trailing character that doesn't complete a byte.
+    assert_eq!(
+        Cid::from_str("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdia"),
+        Err(CidError::InvalidBaseEncoding)
+    );
+    // Valid encodings of invalid CIDs.
+    assert_eq!(
+        Cid::from_str("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbz"),
+        Err(CidError::InvalidDigestLength {
+            expected: 32,
+            actual: 31
+        })
+    );
+    assert_eq!(Cid::from_str("f0270"), Err(CidError::UnknownVersion(2)));
+}
+
+#[test]
+fn cid_in_sets() {
+    let first = Cid::try_from(&CID_V1_DAG_PB[..]).unwrap();
+    let second =
+        Cid::from_str("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").unwrap();
+    let mut set = HashSet::new();
+    set.insert(first.clone());
+    set.insert(second.clone());
+    assert_eq!(set.len(), 1);
+
+    // Ordered by the binary form, so CIDv0 (starting with 0x12) comes after CIDv1.
+    let v0 = Cid::from_str("QmbWqxBEKC3P8tqsKc98xmWNzrzDtRLMiMPL8wBuTGsMnR").unwrap();
+    let sorted: Vec<_> = vec![v0.clone(), first.clone(), second]
+        .into_iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    assert_eq!(sorted, vec![first, v0]);
+}
+
+#[test]
+fn sort_cids_by_bytes() {
+    let mut cids: Vec<Cid> = [&b"c"[..], b"a", b"b"]
+        .iter()
+        .map(|data| Cid::new_v1(0x71, Code::Sha2_256.digest(data)))
+        .collect();
+    cids.push(Cid::new_v1(0x55, Code::Identity.digest(b"raw")));
+    // Varints don't sort numerically: 0x81 is `0x81 0x01`, 0x100 is `0x80 0x02`.
+    cids.push(Cid::new_v1(0x81, Code::Identity.digest(b"x")));
+    cids.push(Cid::new_v1(0x100, Code::Identity.digest(b"x")));
+    cids.push(Cid::new_v1(0x55, Multihash::new(0x81, vec![1])));
+    cids.push(Cid::new_v1(0x55, Multihash::new(0x100, vec![1])));
+    cids.push(Cid::new_v1(0x55, Multihash::new(0x00, vec![1; 0x81])));
+    cids.push(Cid::new_v1(0x55, Multihash::new(0x00, vec![1; 0x100])));
+    cids.push(Cid::new_v0(Code::Sha2_256.digest(b"a")).unwrap());
+    cids.push(Cid::new_v0(Code::Sha2_256.digest(b"b")).unwrap());
+    cids.sort();
+    let bytes: Vec<Vec<u8>> = cids.iter().map(Cid::to_bytes).collect();
+    let mut sorted_bytes = bytes.clone();
+    sorted_bytes.sort();
+    assert_eq!(bytes, sorted_bytes);
+
+    // CIDs work as map keys, e.g. to store blocks by their CID.
+    let blocks: BTreeMap<Cid, usize> = cids.iter().cloned().zip(0..).collect();
+    assert_eq!(
+        blocks.keys().collect::<Vec<_>>(),
+        cids.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn cid_encoded_len() {
+    let cids = vec![
+        Cid::try_from(&CID_V1_DAG_PB[..]).unwrap(),
+        Cid::new_v0(Code::Sha2_256.digest(b"a")).unwrap(),
+        Cid::new_v1(0x300000, Multihash::new(0x00, vec![1; 200])),
+        Cid::new_v1(0x71, Multihash::new(0x00, vec![])),
+    ];
+    for cid in cids {
+        assert_eq!(cid.encoded_len(), cid.to_bytes().len(), "{}", cid);
+    }
+}
+
+#[test]
+fn cid_link_bytes() {
+    let cid = Cid::try_from(&CID_V1_DAG_PB[..]).unwrap();
+    let link = cid.link_bytes();
+    assert_eq!(link[0], MULTIBASE_IDENTITY);
+    assert_eq!(link[1..], CID_V1_DAG_PB);
+    assert_eq!(link[1..], cid.to_bytes()[..]);
+}
+
+#[test]
+fn cid_into_ipld() {
+    let cid = Cid::try_from(&CID_V1_DAG_PB[..]).unwrap();
+    assert_eq!(Ipld::from(cid.clone()), Ipld::Link(cid.clone()));
+    assert_eq!(Cid::try_from(Ipld::Link(cid.clone())), Ok(cid));
+}
+
+#[test]
+fn compute_multihash() {
+    // SHA2-256 of the empty string.
+    let hash = Code::Sha2_256.digest(b"");
+    assert_eq!(hash.code(), multihash::SHA2_256);
+    assert_eq!(hash.digest()[..4], [0xe3, 0xb0, 0xc4, 0x42]);
+    assert_eq!(Code::Sha2_512.digest(b"").digest().len(), 64);
+    assert_eq!(
+        Code::Identity.digest(b"ab").to_bytes(),
+        vec![0x00, 0x02, b'a', b'b']
+    );
+    for code in [Code::Identity, Code::Sha2_256, Code::Sha2_512] {
+        assert_eq!(Code::from_code(code.code()), Some(code));
+    }
+    assert_eq!(Code::from_code(0x1e), None);
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "cid")]
+
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use ipld_core::{Cid, Multihash, Version};
+
+#[test]
+fn roundtrip_cid_crate() {
+    for string in &[
+        "QmbWqxBEKC3P8tqsKc98xmWNzrzDtRLMiMPL8wBuTGsMnR",
+        "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+        "bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua",
+    ] {
+        let foreign = cid::Cid::from_str(string).unwrap();
+        let cid = Cid::from(foreign);
+        assert_eq!(cid, Cid::from_str(string).unwrap());
+        assert_eq!(cid.codec(), foreign.codec());
+        assert_eq!(cid.hash().code(), foreign.hash().code());
+        assert_eq!(cid.hash().digest(), foreign.hash().digest());
+        assert_eq!(
+            cid.version() == Version::V0,
+            foreign.version() == cid::Version::V0
+        );
+        assert_eq!(cid::Cid::try_from(cid).unwrap(), foreign);
+    }
+}
+
+#[test]
+fn digest_too_long_for_cid_crate() {
+    let cid = Cid::new_v1(0x55, Multihash::new(0x00, vec![0; 65]));
+    assert!(cid::Cid::try_from(cid).is_err());
+}
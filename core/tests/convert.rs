@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use ipld_core::{Cid, ConversionError, Ipld, Multihash};
+
+fn wrong_variant(expected: &'static str, found: &'static str) -> ConversionError {
+    ConversionError::WrongVariant { expected, found }
+}
+
+#[test]
+fn convert_variants() {
+    assert_eq!(bool::try_from(Ipld::Bool(true)), Ok(true));
+    assert_eq!(f64::try_from(Ipld::Float(1.5)), Ok(1.5));
+    assert_eq!(
+        String::try_from(Ipld::String("a".to_string())),
+        Ok("a".to_string())
+    );
+    assert_eq!(Vec::<u8>::try_from(Ipld::Bytes(vec![1])), Ok(vec![1]));
+    assert_eq!(
+        Vec::<Ipld>::try_from(Ipld::List(vec![Ipld::Null])),
+        Ok(vec![Ipld::Null])
+    );
+    let mut map = BTreeMap::new();
+    map.insert("a".to_string(), Ipld::Null);
+    assert_eq!(
+        BTreeMap::<String, Ipld>::try_from(Ipld::Map(map.clone())),
+        Ok(map)
+    );
+}
+
+#[test]
+fn convert_wrong_variants() {
+    assert_eq!(
+        bool::try_from(Ipld::Null),
+        Err(wrong_variant("Bool", "Null"))
+    );
+    assert_eq!(
+        f64::try_from(Ipld::Integer(1)),
+        Err(wrong_variant("Float", "Integer"))
+    );
+    assert_eq!(
+        String::try_from(Ipld::Bytes(vec![])),
+        Err(wrong_variant("String", "Bytes"))
+    );
+    // Links aren't bytes and bytes aren't links.
+    let cid = Cid::new_v1(0x71, Multihash::new(0x00, vec![1]));
+    assert_eq!(
+        Vec::<u8>::try_from(Ipld::Link(cid.clone())),
+        Err(wrong_variant("Bytes", "Link"))
+    );
+    assert_eq!(
+        Cid::try_from(Ipld::Bytes(cid.to_bytes())),
+        Err(wrong_variant("Link", "Bytes"))
+    );
+    assert_eq!(
+        Vec::<Ipld>::try_from(Ipld::Map(BTreeMap::new())),
+        Err(wrong_variant("List", "Map"))
+    );
+    assert_eq!(
+        BTreeMap::<String, Ipld>::try_from(Ipld::List(vec![])),
+        Err(wrong_variant("Map", "List"))
+    );
+    assert_eq!(
+        i64::try_from(Ipld::Float(1.0)),
+        Err(wrong_variant("Integer", "Float"))
+    );
+    assert_eq!(
+        wrong_variant("Integer", "Float").to_string(),
+        "expected Integer, found Float"
+    );
+}
+
+#[test]
+fn convert_integers() {
+    assert_eq!(i128::try_from(Ipld::Integer(i128::MIN)), Ok(i128::MIN));
+    assert_eq!(i64::try_from(Ipld::Integer(-1)), Ok(-1));
+    assert_eq!(u64::try_from(Ipld::Integer(u64::MAX.into())), Ok(u64::MAX));
+    assert_eq!(u8::try_from(Ipld::Integer(255)), Ok(255));
+    assert_eq!(
+        u64::try_from(Ipld::Integer(-1)),
+        Err(ConversionError::IntegerOutOfRange {
+            value: -1,
+            target: "u64"
+        })
+    );
+    assert_eq!(
+        i64::try_from(Ipld::Integer(i128::from(i64::MAX) + 1)),
+        Err(ConversionError::IntegerOutOfRange {
+            value: i128::from(i64::MAX) + 1,
+            target: "i64"
+        })
+    );
+    let error = u8::try_from(Ipld::Integer(256)).unwrap_err();
+    assert_eq!(error.to_string(), "integer 256 is out of range for u8");
+}
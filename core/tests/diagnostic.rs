@@ -0,0 +1,35 @@
+use ipld_core::{ipld, Cid, Ipld, Multihash};
+
+#[test]
+fn display_every_variant() {
+    let cid = Cid::new_v1(0x71, Multihash::new(0x00, vec![0x01]));
+    let ipld = ipld!({
+        "list": [null, true, false, -1, 18446744073709551615u64, 1.5, -0.0, 1e300, 0.1],
+        "bytes": Bytes(vec![0x01, 0x02, 0xff]),
+        "link": Link(cid),
+        "text": "\"quoted\"\n\t\u{1}\u{7f}\\ ünïcödé 🦀",
+        "a": {},
+        "": [],
+    });
+    assert_eq!(
+        ipld.to_string(),
+        concat!(
+            r#"{"": [], "a": {}, "link": 42(h'000171000101'), "list": [null, true, false, -1, "#,
+            r#"18446744073709551615, 1.5, -0.0, 1e300, 0.1], "text": "\"quoted\"\n\t\u0001"#,
+            r#"\u007f\\ ünïcödé 🦀", "bytes": h'0102ff'}"#
+        )
+    );
+}
+
+#[test]
+fn display_floats() {
+    assert_eq!(Ipld::Float(1.0).to_string(), "1.0");
+    assert_eq!(Ipld::Float(f64::NAN).to_string(), "NaN");
+    assert_eq!(Ipld::Float(f64::INFINITY).to_string(), "Infinity");
+    assert_eq!(Ipld::Float(f64::NEG_INFINITY).to_string(), "-Infinity");
+    // Floats survive a roundtrip through their text.
+    for value in &[0.1, 1.0 / 3.0, f64::MIN_POSITIVE, f64::MAX, 5e-324] {
+        let text = Ipld::Float(*value).to_string();
+        assert_eq!(text.parse::<f64>().unwrap(), *value);
+    }
+}
@@ -0,0 +1,75 @@
+use ipld_core::{diff, ipld, DiffKind, Ipld};
+
+#[test]
+fn diff_nested_maps() {
+    let a = ipld!({
+        "meta": { "version": 1, "name": "doc", "tags": ["a", "b"] },
+        "items": [1, 2, 3],
+        "size": 10,
+        "old": null,
+    });
+    let b = ipld!({
+        "meta": { "version": 2, "name": "doc", "tags": ["a"], "author": "x" },
+        "items": [1, 2.0, 3, 4],
+        "size": 10,
+        "key/with/slash": true,
+    });
+    assert_eq!(
+        diff(&a, &b),
+        vec![
+            ("items/1".to_string(), DiffKind::ChangedType),
+            ("items/3".to_string(), DiffKind::Added),
+            ("key%2Fwith%2Fslash".to_string(), DiffKind::Added),
+            ("meta/author".to_string(), DiffKind::Added),
+            ("meta/tags/1".to_string(), DiffKind::Removed),
+            ("meta/version".to_string(), DiffKind::ChangedValue),
+            ("old".to_string(), DiffKind::Removed),
+        ]
+    );
+    assert_eq!(diff(&a, &a), vec![]);
+    // The paths resolve in the values that have them.
+    for (path, kind) in diff(&a, &b) {
+        assert_eq!(
+            a.resolve(&path).is_ok(),
+            kind != DiffKind::Added,
+            "{}",
+            path
+        );
+        assert_eq!(
+            b.resolve(&path).is_ok(),
+            kind != DiffKind::Removed,
+            "{}",
+            path
+        );
+    }
+}
+
+#[test]
+fn diff_top_level() {
+    assert_eq!(
+        diff(&ipld!(1), &ipld!(2)),
+        vec![(String::new(), DiffKind::ChangedValue)]
+    );
+    assert_eq!(
+        diff(&ipld!([]), &ipld!({})),
+        vec![(String::new(), DiffKind::ChangedType)]
+    );
+    assert_eq!(
+        diff(&ipld!(0.0), &ipld!(-0.0)),
+        vec![(String::new(), DiffKind::ChangedValue)]
+    );
+}
+
+#[test]
+fn diff_deeply_nested() {
+    let mut a = Ipld::Integer(1);
+    let mut b = Ipld::Integer(2);
+    for _ in 0..10_000 {
+        a = Ipld::List(vec![a]);
+        b = Ipld::List(vec![b]);
+    }
+    let differences = diff(&a, &b);
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].0, ["0"; 10_000].join("/"));
+    assert_eq!(differences[0].1, DiffKind::ChangedValue);
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "json")]
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use ipld_core::{BytesAndLinks, Cid, Ipld, JsonError, JsonOptions, MAX_SAFE_INTEGER};
+use serde_json::{json, Value};
+
+const CID: &str = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+
+fn document() -> Ipld {
+    let mut map = BTreeMap::new();
+    map.insert("data".to_string(), Ipld::Bytes(vec![1, 2, 3, 4]));
+    map.insert("link".to_string(), Ipld::Link(Cid::from_str(CID).unwrap()));
+    map.insert(
+        "list".to_string(),
+        Ipld::List(vec![
+            Ipld::Null,
+            Ipld::Bool(true),
+            Ipld::Integer(-1),
+            Ipld::Integer(MAX_SAFE_INTEGER),
+            Ipld::Float(1.5),
+            Ipld::String("x".to_string()),
+        ]),
+    );
+    Ipld::Map(map)
+}
+
+#[test]
+fn roundtrip_json() {
+    let json = Value::try_from(document()).unwrap();
+    assert_eq!(
+        json,
+        json!({
+            "data": {"/": {"bytes": "AQIDBA"}},
+            "link": {"/": CID},
+            "list": [null, true, -1, 9007199254740991u64, 1.5, "x"],
+        })
+    );
+    assert_eq!(Ipld::try_from(json).unwrap(), document());
+}
+
+#[test]
+fn plain_objects_with_slash() {
+    // Only the exact reserved shapes are treated specially.
+    let json = json!({"/": "x", "other": 1});
+    assert!(Ipld::try_from(json).unwrap().is_map());
+    let json = json!({"/": {"bytes": "AQ", "other": 1}});
+    assert!(Ipld::try_from(json).unwrap()["/"].is_map());
+}
+
+#[test]
+fn safe_integers() {
+    for value in &[MAX_SAFE_INTEGER, -MAX_SAFE_INTEGER] {
+        let json = Value::try_from(Ipld::Integer(*value)).unwrap();
+        assert_eq!(Ipld::try_from(json).unwrap(), Ipld::Integer(*value));
+    }
+    for value in &[
+        1 << 53,
+        -(1 << 53),
+        u64::MAX.into(),
+        i64::MIN.into(),
+        i128::MIN,
+        i128::MAX,
+    ] {
+        assert_eq!(
+            Value::try_from(Ipld::Integer(*value)),
+            Err(JsonError::IntegerOutOfRange(*value))
+        );
+    }
+
+    let options = JsonOptions {
+        allow_unsafe_integers: true,
+        ..Default::default()
+    };
+    for value in &[(1 << 53) + 1, u64::MAX.into(), i64::MIN.into()] {
+        let json = Ipld::Integer(*value)
+            .into_json_with_options(&options)
+            .unwrap();
+        assert_eq!(Ipld::try_from(json).unwrap(), Ipld::Integer(*value));
+    }
+    for value in &[i128::from(u64::MAX) + 1, i128::MIN, i128::MAX] {
+        assert_eq!(
+            Ipld::Integer(*value).into_json_with_options(&options),
+            Err(JsonError::IntegerOutOfRange(*value))
+        );
+    }
+}
+
+#[test]
+fn reject_bytes_and_links() {
+    let options = JsonOptions {
+        bytes_and_links: BytesAndLinks::Reject,
+        ..Default::default()
+    };
+    assert_eq!(
+        document().into_json_with_options(&options),
+        Err(JsonError::NotAllowed("bytes"))
+    );
+    assert_eq!(
+        Ipld::Link(Cid::from_str(CID).unwrap())
+            .into_json_with_options(&options)
+            .unwrap_err()
+            .to_string(),
+        "links can't be converted into JSON"
+    );
+    let mut plain = BTreeMap::new();
+    plain.insert("list".to_string(), Ipld::List(vec![Ipld::Integer(1)]));
+    assert_eq!(
+        Ipld::Map(plain).into_json_with_options(&options).unwrap(),
+        json!({"list": [1]})
+    );
+}
+
+#[test]
+fn json_errors() {
+    assert!(matches!(
+        Value::try_from(Ipld::Float(f64::INFINITY)),
+        Err(JsonError::NonFiniteFloat(_))
+    ));
+    assert!(matches!(
+        Ipld::try_from(json!({"/": "not a cid"})),
+        Err(JsonError::InvalidLink(_))
+    ));
+    assert_eq!(
+        Ipld::try_from(json!({"/": {"bytes": "!!"}})),
+        Err(JsonError::InvalidBytes)
+    );
+}
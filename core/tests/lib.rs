@@ -0,0 +1,911 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+
+use ipld_core::{ipld, Cid, ConversionError, Ipld, Multihash, Path, PathError, WalkControl};
+
+/// A DAG-CBOR CID with an identity hash of a single byte.
+fn cid(byte: u8) -> Cid {
+    Cid::new_v1(0x71, Multihash::new(0x00, vec![byte]))
+}
+
+/// Asserts that `bytes` is the buffer that started at `original`, i.e. that it was moved, not
+/// cloned.
+fn assert_moved(bytes: &[u8], original: *const u8) {
+    assert_eq!(bytes.as_ptr(), original, "the buffer was cloned");
+}
+
+fn all_variants() -> Vec<Ipld> {
+    vec![
+        Ipld::Null,
+        Ipld::Bool(true),
+        Ipld::Integer(1),
+        Ipld::Float(1.5),
+        Ipld::String("a".to_string()),
+        Ipld::Bytes(vec![1]),
+        Ipld::List(vec![]),
+        Ipld::Map(BTreeMap::new()),
+        Ipld::Link(cid(1)),
+    ]
+}
+
+#[test]
+fn accessors() {
+    assert_eq!(Ipld::Bool(true).as_bool(), Some(true));
+    assert_eq!(Ipld::Integer(-3).as_integer(), Some(-3));
+    assert_eq!(Ipld::Float(1.5).as_float(), Some(1.5));
+    assert_eq!(Ipld::String("x".to_string()).as_str(), Some("x"));
+    assert_eq!(Ipld::Bytes(vec![1, 2]).as_bytes(), Some(&[1, 2][..]));
+    assert_eq!(
+        Ipld::List(vec![Ipld::Null]).as_list(),
+        Some(&[Ipld::Null][..])
+    );
+    let map = BTreeMap::from_iter(vec![("a".to_string(), Ipld::Null)]);
+    assert_eq!(Ipld::Map(map.clone()).as_map(), Some(&map));
+    assert_eq!(Ipld::Link(cid(3)).as_link(), Some(&cid(3)));
+}
+
+#[test]
+fn accessors_mismatched_variant() {
+    for ipld in all_variants() {
+        if !matches!(ipld, Ipld::Bool(_)) {
+            assert_eq!(ipld.as_bool(), None);
+        }
+        if !matches!(ipld, Ipld::Integer(_)) {
+            assert_eq!(ipld.as_integer(), None);
+        }
+        if !matches!(ipld, Ipld::Float(_)) {
+            assert_eq!(ipld.as_float(), None);
+        }
+        if !matches!(ipld, Ipld::String(_)) {
+            assert_eq!(ipld.as_str(), None);
+        }
+        if !matches!(ipld, Ipld::Bytes(_)) {
+            assert_eq!(ipld.as_bytes(), None);
+        }
+        if !matches!(ipld, Ipld::List(_)) {
+            assert_eq!(ipld.as_list(), None);
+        }
+        if !matches!(ipld, Ipld::Map(_)) {
+            assert_eq!(ipld.as_map(), None);
+        }
+        if !matches!(ipld, Ipld::Link(_)) {
+            assert_eq!(ipld.as_link(), None);
+        }
+    }
+}
+
+#[test]
+fn predicates() {
+    for ipld in all_variants() {
+        assert_eq!(ipld.is_null(), matches!(ipld, Ipld::Null));
+        assert_eq!(ipld.is_bool(), matches!(ipld, Ipld::Bool(_)));
+        assert_eq!(ipld.is_integer(), matches!(ipld, Ipld::Integer(_)));
+        assert_eq!(ipld.is_float(), matches!(ipld, Ipld::Float(_)));
+        assert_eq!(ipld.is_string(), matches!(ipld, Ipld::String(_)));
+        assert_eq!(ipld.is_bytes(), matches!(ipld, Ipld::Bytes(_)));
+        assert_eq!(ipld.is_list(), matches!(ipld, Ipld::List(_)));
+        assert_eq!(ipld.is_map(), matches!(ipld, Ipld::Map(_)));
+        assert_eq!(ipld.is_link(), matches!(ipld, Ipld::Link(_)));
+    }
+    // Exactly one predicate holds for each variant.
+    for ipld in all_variants() {
+        let holds = [
+            ipld.is_null(),
+            ipld.is_bool(),
+            ipld.is_integer(),
+            ipld.is_float(),
+            ipld.is_string(),
+            ipld.is_bytes(),
+            ipld.is_list(),
+            ipld.is_map(),
+            ipld.is_link(),
+        ];
+        assert_eq!(holds.iter().filter(|holds| **holds).count(), 1);
+    }
+}
+
+fn nested() -> Ipld {
+    let mut map = BTreeMap::new();
+    map.insert(
+        "items".to_string(),
+        Ipld::List(vec![Ipld::String("first".to_string()), Ipld::Integer(2)]),
+    );
+    map.insert("name".to_string(), Ipld::String("nested".to_string()));
+    Ipld::Map(map)
+}
+
+#[test]
+fn get_chained() {
+    let node = nested();
+    let first = node.get("items").and_then(|items| items.get_index(0));
+    assert_eq!(first, Some(&Ipld::String("first".to_string())));
+    assert_eq!(
+        node.get("items").unwrap().get_index(1),
+        Some(&Ipld::Integer(2))
+    );
+}
+
+#[test]
+fn get_missing() {
+    let node = nested();
+    assert_eq!(node.get("missing"), None);
+    assert_eq!(node.get("items").unwrap().get_index(2), None);
+    assert_eq!(node.get_index(0), None);
+    assert_eq!(node.get("name").unwrap().get("name"), None);
+}
+
+#[test]
+fn index_present() {
+    let node = nested();
+    assert_eq!(node["items"][0], Ipld::String("first".to_string()));
+    assert_eq!(node["name"], Ipld::String("nested".to_string()));
+}
+
+#[test]
+fn index_missing() {
+    let node = nested();
+    assert_eq!(node["missing"], Ipld::Null);
+    assert_eq!(node["missing"]["deeper"][3], Ipld::Null);
+    assert_eq!(node["items"][2], Ipld::Null);
+}
+
+#[test]
+fn index_wrong_variant() {
+    let node = nested();
+    assert_eq!(node[0], Ipld::Null);
+    assert_eq!(node["name"]["name"], Ipld::Null);
+    assert_eq!(node["items"]["first"], Ipld::Null);
+    assert_eq!(Ipld::Null["a"], Ipld::Null);
+}
+
+fn document() -> Ipld {
+    let mut bar = BTreeMap::new();
+    bar.insert(
+        "bar".to_string(),
+        Ipld::List(vec![Ipld::Integer(0), nested()]),
+    );
+    let mut root = BTreeMap::new();
+    root.insert("foo".to_string(), Ipld::Map(bar));
+    Ipld::Map(root)
+}
+
+#[test]
+fn resolve_path() {
+    let root = document();
+    assert_eq!(root.resolve("foo/bar/0"), Ok(&Ipld::Integer(0)));
+    assert_eq!(
+        root.resolve("foo/bar/1/items/0"),
+        Ok(&Ipld::String("first".to_string()))
+    );
+    assert_eq!(root.resolve("/foo/bar/0/"), Ok(&Ipld::Integer(0)));
+    assert_eq!(root.resolve(""), Ok(&root));
+}
+
+#[test]
+fn resolve_path_fails() {
+    let root = document();
+    assert_eq!(
+        root.resolve("foo/baz"),
+        Err(PathError::KeyNotFound {
+            segment: "baz".to_string()
+        })
+    );
+    assert_eq!(
+        root.resolve("foo/bar/2"),
+        Err(PathError::IndexOutOfBounds { index: 2, len: 2 })
+    );
+    assert_eq!(
+        root.resolve("foo/bar/first"),
+        Err(PathError::InvalidIndex {
+            segment: "first".to_string()
+        })
+    );
+    // Dead-ends at the integer.
+    assert_eq!(
+        root.resolve("foo/bar/0/more"),
+        Err(PathError::NotTraversable {
+            segment: "more".to_string()
+        })
+    );
+    let bytes = Ipld::List(vec![Ipld::Bytes(vec![1, 2])]);
+    assert_eq!(
+        bytes.resolve("0/1"),
+        Err(PathError::NotTraversable {
+            segment: "1".to_string()
+        })
+    );
+}
+
+#[test]
+fn resolve_mut() {
+    let mut root = document();
+    *root.resolve_mut("foo/bar/0").unwrap() = Ipld::Integer(10);
+    assert_eq!(root.resolve("foo/bar/0"), Ok(&Ipld::Integer(10)));
+    let path = Path::from_segments(vec!["foo", "bar", "1", "items", "0"]);
+    *root.resolve_path_mut(&path).unwrap() = Ipld::Null;
+    assert_eq!(root.resolve_path(&path), Ok(&Ipld::Null));
+    assert_eq!(
+        root.resolve_mut("foo/bar/2"),
+        Err(PathError::IndexOutOfBounds { index: 2, len: 2 })
+    );
+    assert_eq!(
+        root.resolve_mut("foo/baz").unwrap_err(),
+        PathError::KeyNotFound {
+            segment: "baz".to_string()
+        }
+    );
+}
+
+#[test]
+fn get_index_mut() {
+    let mut list = Ipld::List(vec![Ipld::Integer(1)]);
+    *list.get_index_mut(0).unwrap() = Ipld::Bool(true);
+    assert_eq!(list, Ipld::List(vec![Ipld::Bool(true)]));
+    assert_eq!(list.get_index_mut(1), None);
+    assert_eq!(Ipld::Null.get_index_mut(0), None);
+}
+
+#[test]
+fn resolve_path_across_link() {
+    let mut map = BTreeMap::new();
+    map.insert("parent".to_string(), Ipld::Link(cid(3)));
+    let root = Ipld::List(vec![Ipld::Map(map)]);
+    assert_eq!(
+        root.resolve("0/parent/items/0"),
+        Err(PathError::LinkEncountered {
+            remaining_path: Path::from_segments(vec!["items", "0"]),
+            link: cid(3),
+        })
+    );
+    // A path that ends at the link resolves to it.
+    assert_eq!(root.resolve("0/parent/"), Ok(&Ipld::Link(cid(3))));
+}
+
+#[test]
+fn path_escaping_roundtrip() {
+    let fixtures = vec![
+        (Path::default(), ""),
+        (Path::from_segments(vec!["a/b", ""]), "/a%2Fb/"),
+        (Path::from_segments(vec![""]), "/"),
+        (Path::from_segments(vec!["", ""]), "//"),
+        (Path::from_segments(vec!["/a", "b/"]), "/%2Fa/b%2F"),
+        (Path::from_segments(vec!["100%", "a.b"]), "/100%25/a.b"),
+        (Path::from_segments(vec!["ünïcödé", "🦀"]), "/ünïcödé/🦀"),
+    ];
+    for (path, string) in fixtures {
+        assert_eq!(path.to_string(), string);
+        assert_eq!(string.parse::<Path>().unwrap(), path);
+    }
+}
+
+#[test]
+fn parse_path() {
+    // The leading slash is optional.
+    assert_eq!(
+        "foo/bar".parse::<Path>().unwrap(),
+        Path::from_segments(vec!["foo", "bar"])
+    );
+    assert_eq!(
+        "/%C3%BC%2f".parse::<Path>().unwrap(),
+        Path::from_segments(vec!["ü/"])
+    );
+    for invalid in &["/50%", "/%zz", "/%ff", "/%+4", "/%-4"] {
+        assert!(matches!(
+            invalid.parse::<Path>(),
+            Err(PathError::InvalidEscape { .. })
+        ));
+    }
+}
+
+#[test]
+fn resolve_escaped_keys() {
+    let mut map = BTreeMap::new();
+    map.insert("a/b".to_string(), Ipld::Integer(1));
+    map.insert("".to_string(), Ipld::Integer(2));
+    map.insert("ünï".to_string(), Ipld::Integer(3));
+    let mut inner = BTreeMap::new();
+    inner.insert("".to_string(), Ipld::Integer(4));
+    map.insert("inner/".to_string(), Ipld::Map(inner));
+    let root = Ipld::Map(map);
+
+    assert_eq!(root.resolve("a%2Fb"), Ok(&Ipld::Integer(1)));
+    assert_eq!(root.resolve("/ünï/"), Ok(&Ipld::Integer(3)));
+    assert_eq!(
+        root.resolve("a/b"),
+        Err(PathError::KeyNotFound {
+            segment: "a".to_string()
+        })
+    );
+    let path = Path::from_segments(vec![""]);
+    assert_eq!(root.resolve_path(&path), Ok(&Ipld::Integer(2)));
+    let path = Path::from_segments(vec!["inner/", ""]);
+    assert_eq!(root.resolve_path(&path), Ok(&Ipld::Integer(4)));
+    assert_eq!(
+        root.resolve_path(&path.to_string().parse().unwrap()),
+        Ok(&Ipld::Integer(4))
+    );
+}
+
+#[test]
+fn items_and_entries() {
+    let list = Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2), Ipld::Integer(3)]);
+    let sum: i128 = list.items().unwrap().filter_map(Ipld::as_integer).sum();
+    assert_eq!(sum, 6);
+    assert!(list.entries().is_none());
+
+    let mut map = BTreeMap::new();
+    map.insert("b".to_string(), Ipld::Bool(true));
+    map.insert("a".to_string(), Ipld::Null);
+    let map = Ipld::Map(map);
+    let entries: Vec<_> = map.entries().unwrap().collect();
+    assert_eq!(entries, vec![("a", &Ipld::Null), ("b", &Ipld::Bool(true))]);
+    assert!(map.items().is_none());
+    assert!(Ipld::Null.items().is_none());
+    assert!(Ipld::Null.entries().is_none());
+}
+
+#[test]
+fn into_owned_variants() {
+    assert_eq!(Ipld::String("x".into()).into_string(), Ok("x".to_string()));
+    assert_eq!(Ipld::Null.into_string(), Err(Ipld::Null));
+    assert_eq!(Ipld::Bytes(vec![1]).into_bytes(), Ok(vec![1]));
+    assert_eq!(Ipld::Link(cid(1)).into_bytes(), Err(Ipld::Link(cid(1))));
+    assert_eq!(Ipld::Link(cid(1)).into_link(), Ok(cid(1)));
+    assert_eq!(
+        Ipld::List(vec![Ipld::Null]).into_list(),
+        Ok(vec![Ipld::Null])
+    );
+    assert_eq!(Ipld::Map(BTreeMap::new()).into_map(), Ok(BTreeMap::new()));
+    assert_eq!(Ipld::Integer(1).into_map(), Err(Ipld::Integer(1)));
+
+    let blob = vec![7; 16];
+    let blob_ptr = blob.as_ptr();
+    assert_moved(&Ipld::Bytes(blob).into_bytes().unwrap(), blob_ptr);
+}
+
+#[test]
+fn take_and_replace() {
+    let blob = vec![7; 1 << 16];
+    let blob_ptr = blob.as_ptr();
+    let mut inner = BTreeMap::new();
+    inner.insert("blob".to_string(), Ipld::Bytes(blob));
+    let mut root = Ipld::List(vec![Ipld::Integer(1), Ipld::Map(inner)]);
+
+    let taken = root.get_mut(1).unwrap().take();
+    assert_eq!(root, Ipld::List(vec![Ipld::Integer(1), Ipld::Null]));
+    assert_moved(taken["blob"].as_bytes().unwrap(), blob_ptr);
+
+    let old = root.get_mut(0).unwrap().replace(taken);
+    assert_eq!(old, Ipld::Integer(1));
+    assert_eq!(root[0]["blob"], Ipld::Bytes(vec![7; 1 << 16]));
+    assert_eq!(root[1], Ipld::Null);
+}
+
+#[test]
+fn links() {
+    let mut map = BTreeMap::new();
+    map.insert("bb".to_string(), Ipld::Link(cid(3)));
+    map.insert("c".to_string(), Ipld::Link(cid(5)));
+    map.insert(
+        "a".to_string(),
+        Ipld::List(vec![
+            Ipld::Link(cid(1)),
+            Ipld::Integer(1),
+            Ipld::Link(cid(2)),
+        ]),
+    );
+    let root = Ipld::List(vec![Ipld::Map(map), Ipld::Link(cid(4))]);
+    let links: Vec<_> = root.links().collect();
+    // Shorter keys come first, like in the DAG-CBOR encoding.
+    assert_eq!(links, vec![&cid(1), &cid(2), &cid(5), &cid(3), &cid(4)]);
+}
+
+#[test]
+fn links_duplicates_and_top_level() {
+    let link = Ipld::Link(cid(1));
+    assert_eq!(link.links().collect::<Vec<_>>(), vec![&cid(1)]);
+    let mut map = BTreeMap::new();
+    map.insert("a".to_string(), Ipld::List(vec![link.clone(), Ipld::Null]));
+    map.insert("b".to_string(), link.clone());
+    let root = Ipld::List(vec![link, Ipld::List(vec![Ipld::Map(map)])]);
+    assert_eq!(root.links().count(), 3);
+    assert!(root.links().all(|link| *link == cid(1)));
+}
+
+#[test]
+fn links_scalar() {
+    assert_eq!(Ipld::Integer(1).links().next(), None);
+    assert_eq!(Ipld::Bytes(vec![1]).links().next(), None);
+    let root = Ipld::List(vec![Ipld::List(vec![]), Ipld::Map(BTreeMap::new())]);
+    assert_eq!(root.links().next(), None);
+}
+
+#[test]
+fn iter_paths() {
+    let mut map = BTreeMap::new();
+    map.insert(
+        "b".to_string(),
+        Ipld::List(vec![Ipld::Null, Ipld::Bool(true)]),
+    );
+    map.insert("a".to_string(), Ipld::Map(BTreeMap::new()));
+    map.insert("c/d".to_string(), Ipld::Integer(1));
+    map.insert("aa".to_string(), Ipld::Null);
+    let root = Ipld::List(vec![Ipld::Map(map), Ipld::Link(cid(1))]);
+    let paths: Vec<String> = root.iter().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(
+        paths,
+        vec!["", "/0", "/0/a", "/0/b", "/0/b/0", "/0/b/1", "/0/aa", "/0/c%2Fd", "/1"]
+    );
+    for (path, ipld) in root.iter() {
+        assert_eq!(root.resolve_path(&path), Ok(ipld));
+    }
+    assert_eq!(
+        Ipld::Integer(1).iter().collect::<Vec<_>>(),
+        vec![(Path::default(), &Ipld::Integer(1))]
+    );
+}
+
+#[test]
+fn iter_deeply_nested() {
+    let mut root = Ipld::Null;
+    for _ in 0..10_000 {
+        root = Ipld::List(vec![root]);
+    }
+    let mut count = 0;
+    for (path, ipld) in root.iter() {
+        assert_eq!(path.segments().len(), count);
+        assert_eq!(ipld.is_null(), count == 10_000);
+        count += 1;
+    }
+    assert_eq!(count, 10_001);
+}
+
+#[test]
+fn walk_counts_nodes() {
+    let ipld = ipld!({
+        "a": [1, 2, {"b": Bytes(vec![0; 10])}],
+        "c": "hello",
+        "d": {},
+    });
+    let mut nodes = 0;
+    let mut bytes = 0;
+    let mut first = None;
+    ipld.walk(&mut |node: &Ipld| {
+        nodes += 1;
+        if first.is_none() {
+            first = Some(node.clone());
+        }
+        match node {
+            Ipld::Bytes(value) => bytes += value.len(),
+            Ipld::String(value) => bytes += value.len(),
+            _ => {}
+        }
+    });
+    // The root, the list and its three items, the bytes, the string and the empty map.
+    assert_eq!(nodes, 8);
+    assert_eq!(bytes, 15);
+    assert_eq!(first, Some(ipld.clone()));
+
+    let mut order = Vec::new();
+    ipld.walk(&mut |node: &Ipld| order.push(node.clone()));
+    let expected: Vec<_> = ipld.iter().map(|(_, node)| node.clone()).collect();
+    assert_eq!(order, expected);
+}
+
+#[test]
+fn walk_mut_replaces_values() {
+    let mut inner = BTreeMap::new();
+    inner.insert("secret".to_string(), Ipld::String("hidden".to_string()));
+    let mut map = BTreeMap::new();
+    map.insert("a".to_string(), Ipld::String("a long string".to_string()));
+    map.insert("b".to_string(), Ipld::Map(inner));
+    let mut root = Ipld::List(vec![Ipld::Map(map), Ipld::String("short".to_string())]);
+
+    let mut visited = Vec::new();
+    root.walk_mut(|path, ipld| {
+        visited.push(path.to_string());
+        if path.to_string() == "/0/b" {
+            // Replace the map with a scalar, there is nothing left to descend into.
+            *ipld = Ipld::Integer(1);
+        } else if let Ipld::String(string) = ipld {
+            string.truncate(5);
+        }
+        WalkControl::Continue
+    });
+    assert_eq!(visited, vec!["", "/0", "/0/a", "/0/b", "/1"]);
+    assert_eq!(root[0]["a"], Ipld::String("a lon".to_string()));
+    assert_eq!(root[0]["b"], Ipld::Integer(1));
+    assert_eq!(root[1], Ipld::String("short".to_string()));
+}
+
+#[test]
+fn walk_mut_replaced_children_are_walked() {
+    let mut root = Ipld::List(vec![Ipld::Null]);
+    let mut visited = Vec::new();
+    root.walk_mut(|path, ipld| {
+        visited.push(path.to_string());
+        if ipld.is_null() {
+            *ipld = Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2)]);
+        }
+        WalkControl::Continue
+    });
+    assert_eq!(visited, vec!["", "/0", "/0/0", "/0/1"]);
+}
+
+#[test]
+fn walk_mut_skip_and_stop() {
+    let large = Ipld::List(vec![Ipld::Integer(0); 10_000]);
+    let mut root = Ipld::List(vec![large, Ipld::Integer(1), Ipld::Integer(2)]);
+    let mut visited = Vec::new();
+    root.walk_mut(|path, ipld| {
+        visited.push(path.to_string());
+        match ipld {
+            Ipld::List(list) if list.len() == 10_000 => WalkControl::SkipChildren,
+            Ipld::Integer(1) => WalkControl::Stop,
+            _ => WalkControl::Continue,
+        }
+    });
+    assert_eq!(visited, vec!["", "/0", "/1"]);
+
+    let mut visited = 0;
+    root.walk_mut(|_, _| {
+        visited += 1;
+        WalkControl::SkipChildren
+    });
+    assert_eq!(visited, 1);
+}
+
+#[test]
+fn from_primitives() {
+    assert_eq!(Ipld::from(true), Ipld::Bool(true));
+    assert_eq!(Ipld::from(42i64), Ipld::Integer(42));
+    assert_eq!(Ipld::from(-42i128), Ipld::Integer(-42));
+    assert_eq!(Ipld::from(u64::MAX), Ipld::Integer(u64::MAX.into()));
+    assert_eq!(Ipld::from(7u8), Ipld::Integer(7));
+    assert_eq!(Ipld::from(1.5f64), Ipld::Float(1.5));
+    assert_eq!(Ipld::from(1.5f32), Ipld::Float(1.5));
+    assert_eq!(Ipld::from("a".to_string()), Ipld::String("a".to_string()));
+    assert_eq!(Ipld::from("a"), Ipld::String("a".to_string()));
+    assert_eq!(Ipld::from(vec![1u8, 2]), Ipld::Bytes(vec![1, 2]));
+    assert_eq!(Ipld::from(&[1u8, 2][..]), Ipld::Bytes(vec![1, 2]));
+    assert_eq!(Ipld::from(i8::MIN), Ipld::Integer(-128));
+    assert_eq!(Ipld::from(u16::MAX), Ipld::Integer(65535));
+    assert_eq!(Ipld::from(-1i32), Ipld::Integer(-1));
+    assert_eq!(Ipld::from(u32::MAX), Ipld::Integer(4_294_967_295));
+    assert_eq!(
+        Ipld::from(u64::MAX).as_integer(),
+        Some(18_446_744_073_709_551_615)
+    );
+    assert_eq!(Ipld::from(Some(1u8)), Ipld::Integer(1));
+    assert_eq!(Ipld::from(Some("a")), Ipld::String("a".to_string()));
+    assert_eq!(Ipld::from(None::<bool>), Ipld::Null);
+    assert_eq!(
+        Ipld::from(vec![Ipld::Null, Ipld::Bool(false)]),
+        Ipld::List(vec![Ipld::Null, Ipld::Bool(false)])
+    );
+}
+
+#[test]
+fn collect_lists_and_maps() {
+    let strings = vec!["a".to_string(), "b".to_string()];
+    assert_eq!(
+        strings.into_iter().collect::<Ipld>(),
+        Ipld::List(vec![
+            Ipld::String("a".to_string()),
+            Ipld::String("b".to_string())
+        ])
+    );
+    // Bytes are only bytes when converted as a whole.
+    assert_eq!(
+        vec![1u8, 2].into_iter().collect::<Ipld>(),
+        Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2)])
+    );
+    assert_eq!(Ipld::from(vec![1u8, 2]), Ipld::Bytes(vec![1, 2]));
+
+    let map: Ipld = vec![("a", 1), ("b", 2)].into_iter().collect();
+    let mut expected = BTreeMap::new();
+    expected.insert("a".to_string(), Ipld::Integer(1));
+    expected.insert("b".to_string(), Ipld::Integer(2));
+    assert_eq!(map, Ipld::from(expected.clone()));
+
+    // The last value of a repeated key wins, like with `BTreeMap::from_iter`.
+    let map: Ipld = vec![("a", 1), ("b", 2), ("a", 3)].into_iter().collect();
+    expected.insert("a".to_string(), Ipld::Integer(3));
+    assert_eq!(map, Ipld::Map(expected));
+}
+
+#[test]
+fn sort_mixed_variants() {
+    let mut values = vec![
+        Ipld::Link(cid(1)),
+        Ipld::Map(BTreeMap::new()),
+        Ipld::List(vec![Ipld::Integer(1)]),
+        Ipld::List(vec![]),
+        Ipld::Bytes(vec![2]),
+        Ipld::String("b".to_string()),
+        Ipld::String("a".to_string()),
+        Ipld::Float(0.0),
+        Ipld::Float(-0.0),
+        Ipld::Float(-1.5),
+        Ipld::Integer(10),
+        Ipld::Integer(-10),
+        Ipld::Bool(true),
+        Ipld::Bool(false),
+        Ipld::Null,
+    ];
+    values.sort();
+    assert_eq!(
+        values,
+        vec![
+            Ipld::Null,
+            Ipld::Bool(false),
+            Ipld::Bool(true),
+            Ipld::Integer(-10),
+            Ipld::Integer(10),
+            Ipld::Float(-1.5),
+            Ipld::Float(-0.0),
+            Ipld::Float(0.0),
+            Ipld::String("a".to_string()),
+            Ipld::String("b".to_string()),
+            Ipld::Bytes(vec![2]),
+            Ipld::List(vec![]),
+            Ipld::List(vec![Ipld::Integer(1)]),
+            Ipld::Map(BTreeMap::new()),
+            Ipld::Link(cid(1)),
+        ]
+    );
+    // An integer never equals a float of the same value.
+    assert!(Ipld::Integer(1) < Ipld::Float(0.5));
+    assert_ne!(Ipld::Integer(1), Ipld::Float(1.0));
+}
+
+#[test]
+fn float_total_order() {
+    assert_eq!(Ipld::Float(f64::NAN), Ipld::Float(f64::NAN));
+    assert_ne!(Ipld::Float(0.0), Ipld::Float(-0.0));
+    assert!(Ipld::Float(f64::NEG_INFINITY) < Ipld::Float(f64::MIN));
+    let mut values = vec![Ipld::Integer(1), Ipld::Integer(1), Ipld::Null];
+    values.sort();
+    values.dedup();
+    assert_eq!(values, vec![Ipld::Null, Ipld::Integer(1)]);
+}
+
+#[test]
+fn order_and_hash_are_consistent() {
+    let nan = f64::from_bits(0x7ff8_0000_0000_0001);
+    let mut values = all_variants();
+    values.extend(vec![
+        Ipld::Float(f64::NAN),
+        Ipld::Float(nan),
+        Ipld::Float(0.0),
+        Ipld::Float(-0.0),
+    ]);
+    for first in &values {
+        assert_eq!(first, &first.clone());
+        assert_eq!(first.cmp(first), std::cmp::Ordering::Equal);
+        for second in &values {
+            assert_eq!(first == second, first.cmp(second).is_eq());
+            if first == second {
+                assert_eq!(hash_of(first), hash_of(second));
+            }
+        }
+    }
+    assert_eq!(
+        hash_of(&Ipld::Float(f64::NAN)),
+        hash_of(&Ipld::Float(f64::NAN))
+    );
+    assert_ne!(Ipld::Float(f64::NAN), Ipld::Float(nan));
+
+    let set: BTreeSet<Ipld> = values.iter().cloned().chain(values.clone()).collect();
+    assert_eq!(set.len(), values.len());
+}
+
+#[test]
+fn sort_is_deterministic() {
+    let mut values = all_variants();
+    values.push(Ipld::Float(-0.0));
+    values.push(Ipld::List(vec![Ipld::Null]));
+    let mut sorted = values.clone();
+    sorted.sort();
+    for rotation in 0..values.len() {
+        let mut shuffled = values.clone();
+        shuffled.rotate_left(rotation);
+        shuffled.reverse();
+        shuffled.sort();
+        assert_eq!(shuffled, sorted);
+    }
+}
+
+#[test]
+fn index_mut_chained() {
+    let mut node = nested();
+    node["items"][1] = Ipld::Integer(3);
+    node["extra"]["deeper"]["deepest"] = Ipld::Bool(true);
+    assert_eq!(node["items"][1], Ipld::Integer(3));
+    assert_eq!(node["extra"]["deeper"]["deepest"], Ipld::Bool(true));
+    assert!(node["extra"].is_map());
+
+    let mut root = document();
+    root["foo"]["bar"][1]["name"] = Ipld::String("renamed".to_string());
+    assert_eq!(
+        root.resolve("foo/bar/1/name"),
+        Ok(&Ipld::String("renamed".to_string()))
+    );
+}
+
+#[test]
+fn index_mut_null() {
+    let mut node = Ipld::Null;
+    node["a"] = Ipld::Integer(1);
+    assert_eq!(
+        node,
+        Ipld::Map(BTreeMap::from_iter(vec![(
+            "a".to_string(),
+            Ipld::Integer(1)
+        )]))
+    );
+}
+
+#[test]
+#[should_panic(expected = "cannot access index 2 of a list of length 2")]
+fn index_mut_out_of_bounds() {
+    let mut node = nested();
+    node["items"][2] = Ipld::Null;
+}
+
+#[test]
+#[should_panic(expected = "cannot access key \"a\" in a non-map value")]
+fn index_mut_wrong_variant() {
+    let mut node = Ipld::Integer(1);
+    node["a"] = Ipld::Null;
+}
+
+fn hash_of(ipld: &Ipld) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    ipld.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn hash_maps_independent_of_insertion_order() {
+    let mut forward = BTreeMap::new();
+    forward.insert("a".to_string(), Ipld::Integer(1));
+    forward.insert("b".to_string(), Ipld::Float(1.5));
+    let mut backward = BTreeMap::new();
+    backward.insert("b".to_string(), Ipld::Float(1.5));
+    backward.insert("a".to_string(), Ipld::Integer(1));
+    let (forward, backward) = (Ipld::Map(forward), Ipld::Map(backward));
+    assert_eq!(forward, backward);
+    assert_eq!(hash_of(&forward), hash_of(&backward));
+
+    let mut seen = HashSet::new();
+    assert!(seen.insert(forward));
+    assert!(!seen.insert(backward));
+}
+
+#[test]
+fn hash_distinguishes_variants() {
+    let values = all_variants();
+    let hashes: HashSet<u64> = values.iter().map(hash_of).collect();
+    assert_eq!(hashes.len(), values.len());
+    assert_ne!(hash_of(&Ipld::Integer(1)), hash_of(&Ipld::Float(1.0)));
+    assert_ne!(hash_of(&Ipld::Bytes(vec![1])), hash_of(&Ipld::Link(cid(1))));
+}
+
+#[test]
+fn get_generic_index() {
+    let node = nested();
+    assert_eq!(
+        node.get("items").and_then(|items| items.get(1)),
+        Some(&Ipld::Integer(2))
+    );
+    assert_eq!(
+        node.get("name".to_string()),
+        Some(&Ipld::String("nested".to_string()))
+    );
+    let key = "name".to_string();
+    assert!(node.get(&key).is_some());
+    // Wrong variants.
+    assert_eq!(node.get(0), None);
+    assert_eq!(node["items"].get("first"), None);
+    assert_eq!(Ipld::Null.get("a"), None);
+    assert_eq!(Ipld::Integer(1).get(0), None);
+}
+
+#[test]
+fn get_mut_generic_index() {
+    let mut node = nested();
+    *node.get_mut("items").unwrap().get_mut(0).unwrap() = Ipld::Null;
+    assert_eq!(node["items"][0], Ipld::Null);
+    assert_eq!(node.get_mut(0), None);
+    assert_eq!(node.get_mut("missing"), None);
+    assert_eq!(node.get_mut("items").unwrap().get_mut(5), None);
+}
+
+#[test]
+fn get_in_path() {
+    let root = document();
+    assert_eq!(
+        root.get_in(&[&"foo", &"bar", &1, &"items", &0]),
+        Some(&Ipld::String("first".to_string()))
+    );
+    assert_eq!(root.get_in(&[]), Some(&root));
+    assert_eq!(root.get_in(&[&"foo", &0]), None);
+    assert_eq!(root.get_in(&[&"foo", &"bar", &"0"]), None);
+    assert_eq!(root.get_in(&[&"foo", &"bar", &7]), None);
+}
+
+#[test]
+fn compare_with_primitives() {
+    let node =
+        ipld!({ "name": "x", "count": 42, "ratio": 0.5, "done": true, "link": Link(cid(1)) });
+    assert_eq!(node.get("name").unwrap(), &"x");
+    assert_eq!(node["name"], *"x");
+    assert_eq!(node["name"], "x".to_string());
+    assert!(node["count"] == 42);
+    assert!(node["count"] == 42u64);
+    assert!(node["count"] == 42i8);
+    assert!(node["ratio"] == 0.5);
+    assert!(node["done"] == true);
+    assert!(node["link"] == cid(1));
+
+    // Other values and other variants are never equal.
+    assert!(node["name"] != "y");
+    assert!(node["count"] != 43);
+    assert!(node["count"] != 42.0);
+    assert!(node["ratio"] != 0);
+    assert!(node["done"] != false);
+    assert!(node["done"] != "true");
+    assert!(node["link"] != cid(2));
+    assert!(Ipld::Null != false);
+    assert!(Ipld::Float(0.0) != -0.0);
+    let nan = f64::NAN;
+    assert!(Ipld::Float(nan) == nan);
+}
+
+#[test]
+fn merge_maps() {
+    let mut doc = ipld!({ "a": 1, "b": { "x": 1 } });
+    doc.merge(ipld!({ "b": { "y": 2 }, "c": 3 })).unwrap();
+    // Overlapping keys are overwritten as a whole, disjoint keys are added.
+    assert_eq!(doc, ipld!({ "a": 1, "b": { "y": 2 }, "c": 3 }));
+    doc.merge(ipld!({})).unwrap();
+    assert_eq!(doc, ipld!({ "a": 1, "b": { "y": 2 }, "c": 3 }));
+
+    assert_eq!(
+        doc.merge(ipld!([1])),
+        Err(ConversionError::WrongVariant {
+            expected: "Map",
+            found: "List"
+        })
+    );
+    let mut list = ipld!([1]);
+    assert_eq!(
+        list.merge(ipld!({ "a": 1 })).unwrap_err().to_string(),
+        "expected Map, found List"
+    );
+    assert_eq!(list, ipld!([1]));
+}
+
+#[test]
+fn into_items_and_entries() {
+    let mut items = Vec::new();
+    for item in ipld!([1, "two", [3]]).into_items().unwrap() {
+        items.push(item);
+    }
+    assert_eq!(items, vec![ipld!(1), ipld!("two"), ipld!([3])]);
+
+    let entries: Vec<(String, Ipld)> = ipld!({ "b": 2, "a": 1 }).into_entries().unwrap().collect();
+    assert_eq!(
+        entries,
+        vec![("a".to_string(), ipld!(1)), ("b".to_string(), ipld!(2))]
+    );
+
+    // Other variants are given back.
+    assert_eq!(ipld!({}).into_items().unwrap_err(), ipld!({}));
+    assert_eq!(ipld!([]).into_entries().unwrap_err(), ipld!([]));
+    assert_eq!(ipld!(null).into_items().unwrap_err(), Ipld::Null);
+    assert_eq!(ipld!([]).into_items().unwrap().next(), None);
+}
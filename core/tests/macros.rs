@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use ipld_core::{ipld, Cid, Ipld};
+
+#[test]
+fn scalars() {
+    assert_eq!(ipld!(null), Ipld::Null);
+    assert_eq!(ipld!(true), Ipld::Bool(true));
+    assert_eq!(ipld!(false), Ipld::Bool(false));
+    assert_eq!(ipld!(1), Ipld::Integer(1));
+    assert_eq!(ipld!(-1), Ipld::Integer(-1));
+    assert_eq!(ipld!(u64::MAX), Ipld::Integer(u64::MAX.into()));
+    assert_eq!(ipld!(1.5), Ipld::Float(1.5));
+    assert_eq!(ipld!("x"), Ipld::String("x".to_string()));
+    assert_eq!(ipld!(Bytes(b"\x01\x02")), Ipld::Bytes(vec![1, 2]));
+    assert_eq!(ipld!(Bytes(&[3u8][..])), Ipld::Bytes(vec![3]));
+    assert_eq!(ipld!(Bytes(vec![4])), Ipld::Bytes(vec![4]));
+}
+
+#[test]
+fn links_from_cids() {
+    let cid = Cid::from_str("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").unwrap();
+    let node = ipld!({ "parent": Link(&cid), "self": Link(cid.clone()) });
+    assert_eq!(node["parent"], Ipld::Link(cid.clone()));
+    assert_eq!(node["self"], Ipld::Link(cid.clone()));
+}
+
+#[test]
+fn interpolated_variables() {
+    let name = "x".to_string();
+    let items = vec![Ipld::Integer(1)];
+    let flag = true;
+    assert_eq!(
+        ipld!([name.clone(), (items.clone()), flag, 1 + 1]),
+        Ipld::List(vec![
+            Ipld::String(name),
+            Ipld::List(items),
+            Ipld::Bool(true),
+            Ipld::Integer(2),
+        ])
+    );
+}
+
+#[test]
+fn lists() {
+    assert_eq!(ipld!([]), Ipld::List(vec![]));
+    assert_eq!(
+        ipld!([1, -2, "three", null, [true],]),
+        Ipld::List(vec![
+            Ipld::Integer(1),
+            Ipld::Integer(-2),
+            Ipld::String("three".to_string()),
+            Ipld::Null,
+            Ipld::List(vec![Ipld::Bool(true)]),
+        ])
+    );
+}
+
+#[test]
+fn maps() {
+    let link =
+        Cid::from_str("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").unwrap();
+    let key = "computed";
+    let value = 2 + 3;
+    let node = ipld!({
+        "name": "x",
+        "items": [1, 2, 3],
+        "link": Link(link.clone()),
+        "nested": { "empty": {} },
+        (key): value,
+    });
+
+    let mut nested = BTreeMap::new();
+    nested.insert("empty".to_string(), Ipld::Map(BTreeMap::new()));
+    let mut map = BTreeMap::new();
+    map.insert("name".to_string(), Ipld::String("x".to_string()));
+    map.insert(
+        "items".to_string(),
+        Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2), Ipld::Integer(3)]),
+    );
+    map.insert("link".to_string(), Ipld::Link(link));
+    map.insert("nested".to_string(), Ipld::Map(nested));
+    map.insert("computed".to_string(), Ipld::Integer(5));
+    assert_eq!(node, Ipld::Map(map));
+}
@@ -0,0 +1,26 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Builds the crate in `tests/no_std_check`, which is `#![no_std]` and depends on `ipld-core`
+/// without default features. Within the workspace the features of all members are unified, so
+/// it is built on its own.
+#[test]
+fn builds_without_std() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO"))
+        .arg("build")
+        .arg("--quiet")
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("tests/no_std_check/Cargo.toml"))
+        .env(
+            "CARGO_TARGET_DIR",
+            manifest_dir.join("../target/no_std_check"),
+        )
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
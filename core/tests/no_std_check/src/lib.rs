@@ -0,0 +1,19 @@
+//! Builds `ipld-core` without its `std` feature from a `no_std` crate, see `tests/no_std.rs`.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+
+use ipld_core::{ipld, Cid, Ipld, Multihash};
+
+pub fn document() -> Ipld {
+    let cid = Cid::new_v1(0x71, Multihash::new(0x00, vec![1]));
+    ipld!({ "name": "a", "links": [Link(cid)], "bytes": Bytes(b"\x01") })
+}
+
+pub fn describe(ipld: &Ipld) -> Option<(String, usize)> {
+    let name = ipld.resolve("name").ok()?.as_str()?;
+    Some((String::from(name), ipld.links().count()))
+}
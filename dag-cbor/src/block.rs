@@ -0,0 +1,70 @@
+//! Encoded data together with its CID.
+use ipld_core::multihash::Code;
+use ipld_core::Cid;
+
+use crate::multihash::{self, Hasher};
+use crate::{DagCbor, Error, DAG_CBOR};
+
+/// A DAG-CBOR encoded value and the CID it is stored under.
+///
+/// The data always hashes to the CID: blocks are either encoded here or checked when they are
+/// created from existing data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    cid: Cid,
+    data: Vec<u8>,
+}
+
+impl Block {
+    /// Encodes `value` into a block with a CIDv1 that is hashed with SHA2-256.
+    pub fn encode<T: DagCbor>(value: &T) -> Result<Self, Error> {
+        Self::encode_with_hash(value, Code::Sha2_256)
+    }
+
+    /// Encodes `value` into a block with a CIDv1 that is hashed with `hasher`, e.g. a [`Code`].
+    pub fn encode_with_hash<T: DagCbor, H: Hasher>(value: &T, hasher: H) -> Result<Self, Error> {
+        let data = value.to_bytes()?;
+        let cid = Cid::new_v1(DAG_CBOR, hasher.digest(&data));
+        Ok(Self { cid, data })
+    }
+
+    /// Creates a block from data that was stored under `cid`, e.g. in a block store.
+    ///
+    /// Fails if the data doesn't hash to the CID. The codec of the CID isn't checked. Only the
+    /// hash functions of [`Code`] are supported, use [`Block::new_with_hasher`] for others.
+    pub fn new(cid: Cid, data: Vec<u8>) -> Result<Self, Error> {
+        if multihash::digest(cid.hash().code(), &data)? != cid.hash().to_bytes() {
+            return Err(Error::HashMismatch { cid });
+        }
+        Ok(Self { cid, data })
+    }
+
+    /// Like [`Block::new`], but the data is hashed with `hasher`, which must be the hash
+    /// function of the CID.
+    pub fn new_with_hasher<H: Hasher>(cid: Cid, data: Vec<u8>, hasher: H) -> Result<Self, Error> {
+        if &hasher.digest(&data) != cid.hash() {
+            return Err(Error::HashMismatch { cid });
+        }
+        Ok(Self { cid, data })
+    }
+
+    /// Returns the CID.
+    pub fn cid(&self) -> &Cid {
+        &self.cid
+    }
+
+    /// Returns the encoded data.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the CID and the encoded data.
+    pub fn into_parts(self) -> (Cid, Vec<u8>) {
+        (self.cid, self.data)
+    }
+
+    /// Decodes the data.
+    pub fn decode<T: DagCbor>(&self) -> Result<T, Error> {
+        T::from_bytes(&self.data)
+    }
+}
@@ -0,0 +1,203 @@
+//! Decoding into [`IpldRef`] without copying strings and bytes.
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::de;
+use serde_cbor::tags::current_cbor_tag;
+
+use ipld_core::{Cid, IpldRef, MULTIBASE_IDENTITY};
+
+use crate::{
+    encode, error, unexpected_tag, validate, DecodeOptions, Error, CBOR_TAG_CID,
+    MISSING_MULTIBASE_PREFIX,
+};
+
+/// Decodes an [`IpldRef`] from DAG-CBOR, its strings and bytes borrow from `data`.
+///
+/// The input is validated with the default [`DecodeOptions`] first, just like [`from_slice`].
+///
+/// [`from_slice`]: crate::from_slice
+pub fn from_slice_borrowed(data: &[u8]) -> Result<IpldRef<'_>, Error> {
+    validate::validate(data, &DecodeOptions::default())?;
+    let mut de = serde_cbor::Deserializer::from_slice(data);
+    Ok(deserialize_borrowed(&mut de)?)
+}
+
+/// Deserializes an [`IpldRef`] from any Serde deserializer that can lend out its input.
+///
+/// Borrowing only works if the deserializer calls `visit_borrowed_str` and
+/// `visit_borrowed_bytes`. `serde_cbor::Deserializer::from_slice` and `serde_cbor::from_slice`
+/// do, as the input lives long enough. `serde_cbor::from_reader` can't, neither can
+/// deserializers that buffer the input, e.g. when going through `serde_cbor::Value` or an
+/// untagged enum. With those this function fails rather than silently copying. It can also be
+/// used as `#[serde(deserialize_with = "ipld_dag_cbor::deserialize_borrowed", borrow)]`.
+///
+/// Unlike [`from_slice_borrowed`] the raw bytes aren't validated, see [`deserialize`].
+///
+/// [`deserialize`]: crate::deserialize
+pub fn deserialize_borrowed<'de, D>(deserializer: D) -> Result<IpldRef<'de>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserializer.deserialize_any(IpldRefVisitor)
+}
+
+struct IpldRefSeed;
+impl<'de> de::DeserializeSeed<'de> for IpldRefSeed {
+    type Value = IpldRef<'de>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserialize_borrowed(deserializer)
+    }
+}
+
+struct IpldRefVisitor;
+impl<'de> de::Visitor<'de> for IpldRefVisitor {
+    type Value = IpldRef<'de>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("any valid CBOR value with borrowed strings and bytes")
+    }
+
+    #[inline]
+    fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(IpldRef::String(value))
+    }
+
+    #[inline]
+    fn visit_borrowed_bytes<E>(self, value: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(IpldRef::Bytes(value))
+    }
+
+    #[inline]
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(IpldRef::Integer(v.into()))
+    }
+
+    #[inline]
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(IpldRef::Integer(v.into()))
+    }
+
+    #[inline]
+    fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if !encode::is_in_range(v) {
+            return Err(de::Error::custom(error::integer_out_of_range(v)));
+        }
+        Ok(IpldRef::Integer(v))
+    }
+
+    #[inline]
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(IpldRef::Bool(v))
+    }
+
+    #[inline]
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_unit()
+    }
+
+    #[inline]
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(IpldRef::Null)
+    }
+
+    #[inline]
+    fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_f64(f64::from(v))
+    }
+
+    #[inline]
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if !v.is_finite() {
+            return Err(de::Error::custom(format!(
+                "{} is not allowed in DAG-CBOR",
+                v
+            )));
+        }
+        Ok(IpldRef::Float(v))
+    }
+
+    #[inline]
+    fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+    where
+        V: de::SeqAccess<'de>,
+    {
+        let mut vec = Vec::new();
+        while let Some(elem) = visitor.next_element_seed(IpldRefSeed)? {
+            vec.push(elem);
+        }
+        Ok(IpldRef::List(vec))
+    }
+
+    #[inline]
+    fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+    where
+        V: de::MapAccess<'de>,
+    {
+        let mut map = BTreeMap::new();
+        while let Some(key) = visitor.next_key_seed(IpldRefSeed)? {
+            let key = match key {
+                IpldRef::String(key) => key,
+                _ => return Err(de::Error::custom("map keys must be strings")),
+            };
+            let value = visitor.next_value_seed(IpldRefSeed)?;
+            if map.insert(key, value).is_some() {
+                return Err(de::Error::custom(format!("duplicate map key {:?}", key)));
+            }
+        }
+        Ok(IpldRef::Map(map))
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        match current_cbor_tag() {
+            Some(CBOR_TAG_CID) => match deserialize_borrowed(deserializer)? {
+                IpldRef::Bytes([MULTIBASE_IDENTITY, link @ ..]) => Cid::try_from(link)
+                    .map(IpldRef::Link)
+                    .map_err(de::Error::custom),
+                IpldRef::Bytes(_) => Err(de::Error::custom(MISSING_MULTIBASE_PREFIX)),
+                _ => Err(de::Error::custom("bytes expected")),
+            },
+            Some(tag) => Err(unexpected_tag(tag)),
+            _ => Err(de::Error::custom("tag expected")),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Serde support for CIDs as DAG-CBOR links.
+//!
+//! `Ipld` and `Cid` are defined in `ipld-core`, so this crate can neither implement foreign
+//! traits like `Serialize` and `Deserialize` for them nor add inherent methods. Use this module
+//! as `#[serde(with = "ipld_dag_cbor::cid")]` on fields of type `Cid` instead, they are then
+//! encoded as tag 42 with the multibase identity prefix. The [`option`], [`vec`](mod@vec) and
+//! [`map`] submodules do the same for `Option<Cid>`, `Vec<Cid>` and `BTreeMap<String, Cid>`
+//! fields. With the `cid` feature, [`compat`] does it for the `Cid` of the `cid` crate.
+use std::convert::TryFrom;
+
+use serde::{de, ser, Deserialize, Serialize};
+use serde_cbor::tags::Tagged;
+
+use ipld_core::Cid;
+
+use crate::{strip_multibase_prefix, unexpected_tag, CBOR_TAG_CID};
+
+/// Serializes a `Cid` as a DAG-CBOR link.
+pub fn serialize<S>(cid: &Cid, ser: S) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+{
+    let bytes = cid.link_bytes();
+    let value = serde_bytes::Bytes::new(&bytes);
+    Tagged::new(Some(CBOR_TAG_CID), &value).serialize(ser)
+}
+
+/// Deserializes a `Cid` from a DAG-CBOR link.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Cid, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    let tagged = Tagged::<serde_bytes::ByteBuf>::deserialize(deserializer)?;
+    match tagged.tag {
+        Some(CBOR_TAG_CID) => {
+            let mut bytes = tagged.value.into_vec();
+            strip_multibase_prefix(&mut bytes)?;
+            Cid::try_from(bytes).map_err(de::Error::custom)
+        }
+        Some(tag) => Err(unexpected_tag(tag)),
+        _ => Err(de::Error::custom("tag expected")),
+    }
+}
+
+/// A `Cid` that serializes as a DAG-CBOR link, so that it can be put into containers.
+struct LinkRef<'a>(&'a Cid);
+
+impl<'a> Serialize for LinkRef<'a> {
+    fn serialize<S>(&self, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serialize(self.0, ser)
+    }
+}
+
+/// A `Cid` that deserializes from a DAG-CBOR link, so that it can be taken out of containers.
+struct Link(Cid);
+
+impl<'de> Deserialize<'de> for Link {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserialize(deserializer).map(Link)
+    }
+}
+
+/// Serde support for `Option<Cid>` fields.
+///
+/// `None` is encoded as `null`. Add `#[serde(default)]` as well if the field may be missing.
+pub mod option {
+    use serde::{de, ser, Deserialize, Serialize};
+
+    use ipld_core::Cid;
+
+    use super::{Link, LinkRef};
+
+    /// Serializes an optional `Cid` as a DAG-CBOR link or `null`.
+    pub fn serialize<S>(cid: &Option<Cid>, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        cid.as_ref().map(LinkRef).serialize(ser)
+    }
+
+    /// Deserializes an optional `Cid` from a DAG-CBOR link or `null`.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Cid>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Ok(Option::<Link>::deserialize(deserializer)?.map(|link| link.0))
+    }
+}
+
+/// Serde support for `Vec<Cid>` fields, encoded as a list of DAG-CBOR links.
+pub mod vec {
+    use serde::{de, ser, Deserialize};
+
+    use ipld_core::Cid;
+
+    use super::{Link, LinkRef};
+
+    /// Serializes the `Cid`s as a list of DAG-CBOR links.
+    pub fn serialize<S>(cids: &[Cid], ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        ser.collect_seq(cids.iter().map(LinkRef))
+    }
+
+    /// Deserializes `Cid`s from a list of DAG-CBOR links.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Cid>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let links = Vec::<Link>::deserialize(deserializer)?;
+        Ok(links.into_iter().map(|link| link.0).collect())
+    }
+}
+
+/// Serde support for `BTreeMap<String, Cid>` fields, encoded as a map of DAG-CBOR links.
+pub mod map {
+    use std::collections::BTreeMap;
+
+    use serde::{de, ser, Deserialize};
+
+    use ipld_core::Cid;
+
+    use super::{Link, LinkRef};
+
+    /// Serializes the `Cid`s as a map of DAG-CBOR links.
+    pub fn serialize<S>(cids: &BTreeMap<String, Cid>, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        ser.collect_map(cids.iter().map(|(key, cid)| (key, LinkRef(cid))))
+    }
+
+    /// Deserializes `Cid`s from a map of DAG-CBOR links.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<BTreeMap<String, Cid>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let links = BTreeMap::<String, Link>::deserialize(deserializer)?;
+        Ok(links.into_iter().map(|(key, link)| (key, link.0)).collect())
+    }
+}
+
+/// Serde support for the `Cid` of the `cid` crate, available with the `cid` feature.
+///
+/// Use it as `#[serde(with = "ipld_dag_cbor::cid::compat")]` on fields of type `cid::Cid`.
+#[cfg(feature = "cid")]
+pub mod compat {
+    use std::convert::TryFrom;
+
+    use serde::{de, ser};
+
+    /// Serializes a `cid::Cid` as a DAG-CBOR link.
+    pub fn serialize<S>(cid: &::cid::Cid, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        super::serialize(&ipld_core::Cid::from(*cid), ser)
+    }
+
+    /// Deserializes a `cid::Cid` from a DAG-CBOR link.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<::cid::Cid, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let cid = super::deserialize(deserializer)?;
+        ::cid::Cid::try_from(cid).map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,86 @@
+//! Serde support for CIDs that are stored in their binary form as `Vec<u8>`.
+//!
+//! Use this module as `#[serde(with = "ipld_dag_cbor::cid_bytes")]` on `Vec<u8>` fields, they
+//! are then encoded as DAG-CBOR links just like [`crate::cid`] encodes `Cid` fields. The bytes
+//! must be a valid CID, which is checked when encoding as well as when decoding. The [`option`]
+//! and [`vec`](mod@vec) submodules do the same for `Option<Vec<u8>>` and `Vec<Vec<u8>>` fields.
+use std::convert::TryFrom;
+
+use serde::{de, ser};
+
+use ipld_core::Cid;
+
+/// Parses the binary form of a CID, so that it can be serialized.
+fn parse<E: ser::Error>(bytes: &[u8]) -> Result<Cid, E> {
+    Cid::try_from(bytes).map_err(ser::Error::custom)
+}
+
+/// Serializes the binary form of a CID as a DAG-CBOR link.
+pub fn serialize<S>(bytes: &[u8], ser: S) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+{
+    crate::cid::serialize(&parse(bytes)?, ser)
+}
+
+/// Deserializes the binary form of a CID from a DAG-CBOR link.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    Ok(crate::cid::deserialize(deserializer)?.to_bytes())
+}
+
+/// Serde support for `Option<Vec<u8>>` fields.
+///
+/// `None` is encoded as `null`. Add `#[serde(default)]` as well if the field may be missing.
+pub mod option {
+    use serde::{de, ser};
+
+    use super::parse;
+
+    /// Serializes the optional binary form of a CID as a DAG-CBOR link or `null`.
+    pub fn serialize<S>(bytes: &Option<Vec<u8>>, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let cid = bytes.as_deref().map(parse).transpose()?;
+        crate::cid::option::serialize(&cid, ser)
+    }
+
+    /// Deserializes the optional binary form of a CID from a DAG-CBOR link or `null`.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Ok(crate::cid::option::deserialize(deserializer)?.map(|cid| cid.to_bytes()))
+    }
+}
+
+/// Serde support for `Vec<Vec<u8>>` fields, encoded as a list of DAG-CBOR links.
+pub mod vec {
+    use serde::{de, ser};
+
+    use super::parse;
+
+    /// Serializes the binary forms of CIDs as a list of DAG-CBOR links.
+    pub fn serialize<S>(bytes: &[Vec<u8>], ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let cids = bytes
+            .iter()
+            .map(|bytes| parse(bytes))
+            .collect::<Result<Vec<_>, _>>()?;
+        crate::cid::vec::serialize(&cids, ser)
+    }
+
+    /// Deserializes the binary forms of CIDs from a list of DAG-CBOR links.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Vec<u8>>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let cids = crate::cid::vec::deserialize(deserializer)?;
+        Ok(cids.iter().map(|cid| cid.to_bytes()).collect())
+    }
+}
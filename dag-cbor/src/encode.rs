@@ -0,0 +1,194 @@
+//! Writes DAG-CBOR directly instead of going through a Serde serializer.
+//!
+//! `serde_cbor` shrinks floats to half or single precision whenever that is lossless, while
+//! DAG-CBOR requires all floats to be 64-bit. Writing the bytes ourselves also keeps the
+//! encoding independent of how a Serde serializer chooses to represent values.
+use std::collections::BTreeMap;
+
+use ipld_core::{Cid, Ipld};
+
+use crate::{EncodeOptions, Error, MapKeyOrder, CBOR_TAG_CID};
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_STRING: u8 = 3;
+const MAJOR_LIST: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+
+const FALSE: u8 = 0xf4;
+const TRUE: u8 = 0xf5;
+const NULL: u8 = 0xf6;
+const FLOAT64: u8 = 0xfb;
+
+/// Returns the entries of a map in the order they need to be encoded.
+pub(crate) fn sorted_entries(
+    map: &BTreeMap<String, Ipld>,
+    order: MapKeyOrder,
+) -> Vec<(&String, &Ipld)> {
+    // A `BTreeMap` already iterates in bytewise order.
+    let mut entries: Vec<_> = map.iter().collect();
+    if order == MapKeyOrder::LengthFirst {
+        entries.sort_by_key(|(key, _)| key.len());
+    }
+    entries
+}
+
+/// Appends the encoding of `ipld` to `out`.
+pub(crate) fn write(ipld: &Ipld, options: &EncodeOptions, out: &mut Vec<u8>) -> Result<(), Error> {
+    match ipld {
+        Ipld::Null => out.push(NULL),
+        Ipld::Bool(false) => out.push(FALSE),
+        Ipld::Bool(true) => out.push(TRUE),
+        Ipld::Integer(integer) => write_integer(*integer, out)?,
+        Ipld::Float(float) => {
+            if !float.is_finite() && !options.allow_non_finite_floats {
+                return Err(Error::InvalidFloat {
+                    value: *float,
+                    offset: None,
+                });
+            }
+            out.push(FLOAT64);
+            out.extend_from_slice(&float.to_bits().to_be_bytes());
+        }
+        Ipld::String(string) => {
+            write_header(MAJOR_STRING, string.len() as u64, out);
+            out.extend_from_slice(string.as_bytes());
+        }
+        Ipld::Bytes(bytes) => {
+            write_header(MAJOR_BYTES, bytes.len() as u64, out);
+            out.extend_from_slice(bytes);
+        }
+        Ipld::List(list) => {
+            write_header(MAJOR_LIST, list.len() as u64, out);
+            for ipld in list {
+                write(ipld, options, out)?;
+            }
+        }
+        Ipld::Map(map) => {
+            write_header(MAJOR_MAP, map.len() as u64, out);
+            for (key, ipld) in sorted_entries(map, options.map_key_order) {
+                write_header(MAJOR_STRING, key.len() as u64, out);
+                out.extend_from_slice(key.as_bytes());
+                write(ipld, options, out)?;
+            }
+        }
+        Ipld::Link(cid) => {
+            let link = cid.link_bytes();
+            write_header(MAJOR_TAG, CBOR_TAG_CID, out);
+            write_header(MAJOR_BYTES, link.len() as u64, out);
+            out.extend_from_slice(&link);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the length of the encoding of `ipld` with the default options, without encoding it.
+///
+/// Fails for the same values as encoding does.
+pub(crate) fn len(ipld: &Ipld) -> Result<usize, Error> {
+    Ok(match ipld {
+        Ipld::Null | Ipld::Bool(_) => 1,
+        Ipld::Integer(integer) => {
+            if !is_in_range(*integer) {
+                return Err(Error::IntegerOutOfRange {
+                    value: *integer,
+                    offset: None,
+                });
+            }
+            let value = if *integer < 0 { -1 - integer } else { *integer };
+            header_len(value as u64)
+        }
+        Ipld::Float(float) => {
+            if !float.is_finite() {
+                return Err(Error::InvalidFloat {
+                    value: *float,
+                    offset: None,
+                });
+            }
+            9
+        }
+        Ipld::String(string) => header_len(string.len() as u64) + string.len(),
+        Ipld::Bytes(bytes) => header_len(bytes.len() as u64) + bytes.len(),
+        Ipld::List(list) => {
+            let mut sum = header_len(list.len() as u64);
+            for ipld in list {
+                sum += len(ipld)?;
+            }
+            sum
+        }
+        Ipld::Map(map) => {
+            let mut sum = header_len(map.len() as u64);
+            for (key, ipld) in map {
+                sum += header_len(key.len() as u64) + key.len() + len(ipld)?;
+            }
+            sum
+        }
+        Ipld::Link(cid) => link_len(cid),
+    })
+}
+
+/// Returns the length of the encoding of a link to `cid`, without encoding it.
+pub(crate) fn link_len(cid: &Cid) -> usize {
+    // The binary form behind the multibase prefix.
+    let link = 1 + cid.encoded_len();
+    header_len(CBOR_TAG_CID) + header_len(link as u64) + link
+}
+
+/// Returns the length of a header with its argument in the shortest possible form.
+fn header_len(value: u64) -> usize {
+    if value < 24 {
+        1
+    } else if value <= u64::from(u8::MAX) {
+        2
+    } else if value <= u64::from(u16::MAX) {
+        3
+    } else if value <= u64::from(u32::MAX) {
+        5
+    } else {
+        9
+    }
+}
+
+/// Whether an integer is within the range of an `i64` or a `u64`. CBOR could store negative
+/// integers down to `-2^64`, but other IPLD implementations reject anything below `i64::MIN`.
+pub(crate) fn is_in_range(integer: i128) -> bool {
+    i128::from(i64::MIN) <= integer && integer <= i128::from(u64::MAX)
+}
+
+fn write_integer(integer: i128, out: &mut Vec<u8>) -> Result<(), Error> {
+    if !is_in_range(integer) {
+        return Err(Error::IntegerOutOfRange {
+            value: integer,
+            offset: None,
+        });
+    }
+    // Negative integers `n` are encoded as `-1 - n`.
+    let (major, value) = if integer < 0 {
+        (MAJOR_NEGATIVE, -1 - integer)
+    } else {
+        (MAJOR_UNSIGNED, integer)
+    };
+    write_header(major, value as u64, out);
+    Ok(())
+}
+
+/// Writes an initial byte with its argument in the shortest possible form.
+fn write_header(major: u8, value: u64, out: &mut Vec<u8>) {
+    let major = major << 5;
+    if value < 24 {
+        out.push(major | value as u8);
+    } else if value <= u64::from(u8::MAX) {
+        out.extend_from_slice(&[major | 24, value as u8]);
+    } else if value <= u64::from(u16::MAX) {
+        out.push(major | 25);
+        out.extend_from_slice(&(value as u16).to_be_bytes());
+    } else if value <= u64::from(u32::MAX) {
+        out.push(major | 26);
+        out.extend_from_slice(&(value as u32).to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
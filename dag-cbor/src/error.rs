@@ -0,0 +1,284 @@
+use std::{fmt, io};
+
+use ipld_core::{Cid, CidError};
+
+/// Errors that can occur when encoding or decoding DAG-CBOR.
+#[derive(Debug)]
+pub enum Error {
+    /// The input isn't well-formed CBOR.
+    Cbor(serde_cbor::Error),
+    /// Reading or writing failed.
+    Io(io::Error),
+    /// Any other error, e.g. one raised while converting between CBOR and `Ipld`.
+    Codec(String),
+    /// A map contains the same key more than once.
+    DuplicateKey {
+        /// The duplicated key.
+        key: String,
+        /// The byte offset of the second occurrence of the key.
+        offset: usize,
+    },
+    /// A link isn't a valid CID.
+    InvalidCid(String),
+    /// A map key doesn't follow the canonical order, only checked in strict mode.
+    UnorderedKey {
+        /// The key that is out of order.
+        key: String,
+        /// The byte offset of the key.
+        offset: usize,
+    },
+    /// A map key isn't a string.
+    NonStringKey {
+        /// The byte offset of the key.
+        offset: usize,
+    },
+    /// A string, byte string, list or map is encoded with an indefinite length.
+    IndefiniteLength {
+        /// The byte offset of the item.
+        offset: usize,
+    },
+    /// An integer, length or tag is encoded in a wider form than necessary.
+    NonMinimalEncoding {
+        /// The byte offset of the item.
+        offset: usize,
+        /// The encoded integer, length or tag. For negative integers `n` this is `-1 - n`.
+        value: u64,
+    },
+    /// An integer is outside the range DAG-CBOR allows, from `i64::MIN` to `u64::MAX`.
+    IntegerOutOfRange {
+        /// The integer.
+        value: i128,
+        /// The byte offset of the integer when decoding, `None` when encoding.
+        offset: Option<usize>,
+    },
+    /// A float is NaN or infinite, which DAG-CBOR doesn't allow.
+    InvalidFloat {
+        /// The float.
+        value: f64,
+        /// The byte offset of the float when decoding, `None` when encoding.
+        offset: Option<usize>,
+    },
+    /// A float was found while decoding with [`DecodeOptions::forbid_floats`].
+    ///
+    /// [`DecodeOptions::forbid_floats`]: crate::DecodeOptions::forbid_floats
+    FloatNotAllowed {
+        /// The byte offset of the float.
+        offset: usize,
+    },
+    /// A float is encoded with less than the 64-bit precision DAG-CBOR requires.
+    NonCanonicalFloat {
+        /// The precision the float is encoded with, 16 or 32.
+        bits: u8,
+        /// The byte offset of the float.
+        offset: usize,
+    },
+    /// A CBOR tag other than 42, which is the only tag DAG-CBOR allows.
+    UnexpectedTag {
+        /// The tag number.
+        tag: u64,
+        /// The byte offset of the tag.
+        offset: usize,
+    },
+    /// A string, byte string, list or map declares a length that can't possibly fit into the
+    /// rest of the input.
+    LengthExceedsInput {
+        /// The declared length.
+        length: u64,
+        /// The byte offset of the item.
+        offset: usize,
+    },
+    /// A list or map has more elements or entries than
+    /// [`DecodeOptions::max_collection_len`] allows.
+    ///
+    /// [`DecodeOptions::max_collection_len`]: crate::DecodeOptions::max_collection_len
+    CollectionTooLong {
+        /// The declared number of elements or entries.
+        length: u64,
+        /// The maximum number.
+        limit: usize,
+        /// The byte offset of the list or map.
+        offset: usize,
+    },
+    /// Values are nested deeper than [`DecodeOptions::max_depth`] allows.
+    ///
+    /// [`DecodeOptions::max_depth`]: crate::DecodeOptions::max_depth
+    MaxDepthExceeded {
+        /// The maximum depth.
+        limit: usize,
+        /// The byte offset of the list, map or tag that exceeds the limit.
+        offset: usize,
+    },
+    /// A simple value other than `false`, `true` and `null`, e.g. `undefined`.
+    UnsupportedSimpleValue {
+        /// The simple value, `undefined` is 23.
+        value: u8,
+        /// The byte offset of the simple value.
+        offset: usize,
+    },
+    /// A CID uses a hash function that can't be computed here.
+    UnsupportedHash {
+        /// The multihash code.
+        code: u64,
+    },
+    /// The data of a block doesn't hash to its CID.
+    HashMismatch {
+        /// The CID the data was expected to hash to.
+        cid: Cid,
+    },
+}
+
+impl Error {
+    /// Returns the byte offset of the item that caused the error, if it is known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            // `serde_cbor` uses 0 for errors without a position.
+            Error::Cbor(error) => match error.offset() {
+                0 => None,
+                offset => Some(offset as usize),
+            },
+            Error::Io(_)
+            | Error::Codec(_)
+            | Error::InvalidCid(_)
+            | Error::UnsupportedHash { .. }
+            | Error::HashMismatch { .. } => None,
+            Error::IntegerOutOfRange { offset, .. } | Error::InvalidFloat { offset, .. } => *offset,
+            Error::DuplicateKey { offset, .. }
+            | Error::UnorderedKey { offset, .. }
+            | Error::NonStringKey { offset }
+            | Error::IndefiniteLength { offset }
+            | Error::NonMinimalEncoding { offset, .. }
+            | Error::FloatNotAllowed { offset }
+            | Error::NonCanonicalFloat { offset, .. }
+            | Error::UnexpectedTag { offset, .. }
+            | Error::LengthExceedsInput { offset, .. }
+            | Error::CollectionTooLong { offset, .. }
+            | Error::MaxDepthExceeded { offset, .. }
+            | Error::UnsupportedSimpleValue { offset, .. } => Some(*offset),
+        }
+    }
+}
+
+/// Errors with a known offset are prefixed with it, e.g.
+/// `error at byte 1337: unexpected CBOR tag 1 in DAG-CBOR`.
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(offset) = self.offset() {
+            write!(fmt, "error at byte {}: ", offset)?;
+        }
+        match self {
+            Error::Cbor(error) => {
+                // Don't repeat the offset `serde_cbor` appends.
+                let message = error.to_string();
+                let suffix = format!(" at offset {}", error.offset());
+                write!(fmt, "{}", message.strip_suffix(&suffix).unwrap_or(&message))
+            }
+            Error::Io(error) => write!(fmt, "{}", error),
+            Error::Codec(message) => write!(fmt, "{}", message),
+            Error::InvalidCid(message) => write!(fmt, "invalid CID: {}", message),
+            Error::DuplicateKey { key, .. } => write!(fmt, "duplicate map key {:?}", key),
+            Error::UnorderedKey { key, .. } => {
+                write!(fmt, "map key {:?} is not in canonical order", key)
+            }
+            Error::NonStringKey { .. } => write!(fmt, "map keys must be strings"),
+            Error::IndefiniteLength { .. } => {
+                write!(fmt, "indefinite-length items are not allowed in DAG-CBOR")
+            }
+            Error::NonMinimalEncoding { value, .. } => {
+                write!(fmt, "{} is not encoded in its shortest form", value)
+            }
+            Error::IntegerOutOfRange { value, .. } => {
+                write!(fmt, "{}", integer_out_of_range(*value))
+            }
+            Error::InvalidFloat { value, .. } => {
+                write!(fmt, "{} is not allowed in DAG-CBOR", value)
+            }
+            Error::FloatNotAllowed { .. } => write!(fmt, "{}", crate::FLOATS_NOT_ALLOWED),
+            Error::NonCanonicalFloat { bits, .. } => {
+                write!(fmt, "{}-bit float, DAG-CBOR requires 64-bit floats", bits)
+            }
+            Error::UnexpectedTag { tag, .. } => {
+                write!(fmt, "unexpected CBOR tag {} in DAG-CBOR", tag)
+            }
+            Error::LengthExceedsInput { length, .. } => {
+                write!(fmt, "length {} exceeds the remaining input", length)
+            }
+            Error::CollectionTooLong { length, limit, .. } => write!(
+                fmt,
+                "{} elements exceed the limit of {} per list or map",
+                length, limit
+            ),
+            Error::MaxDepthExceeded { limit, .. } => {
+                write!(fmt, "values are nested deeper than {} levels", limit)
+            }
+            Error::UnsupportedSimpleValue { value: 23, .. } => {
+                write!(fmt, "undefined is not supported in DAG-CBOR")
+            }
+            Error::UnsupportedSimpleValue { value, .. } => {
+                write!(fmt, "simple value {} is not supported in DAG-CBOR", value)
+            }
+            Error::UnsupportedHash { code } => {
+                write!(fmt, "unsupported multihash code {:#x}", code)
+            }
+            Error::HashMismatch { cid } => write!(fmt, "the data doesn't hash to {}", cid),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Cbor(error) => Some(error),
+            Error::Io(error) => Some(error),
+            Error::Codec(_)
+            | Error::InvalidCid(_)
+            | Error::DuplicateKey { .. }
+            | Error::UnorderedKey { .. }
+            | Error::NonStringKey { .. }
+            | Error::IndefiniteLength { .. }
+            | Error::NonMinimalEncoding { .. }
+            | Error::IntegerOutOfRange { .. }
+            | Error::InvalidFloat { .. }
+            | Error::FloatNotAllowed { .. }
+            | Error::NonCanonicalFloat { .. }
+            | Error::UnexpectedTag { .. }
+            | Error::LengthExceedsInput { .. }
+            | Error::CollectionTooLong { .. }
+            | Error::MaxDepthExceeded { .. }
+            | Error::UnsupportedSimpleValue { .. }
+            | Error::UnsupportedHash { .. }
+            | Error::HashMismatch { .. } => None,
+        }
+    }
+}
+
+/// The message of [`Error::IntegerOutOfRange`], shared with the Serde serializer.
+pub(crate) fn integer_out_of_range(value: i128) -> String {
+    format!(
+        "integer {} is outside the 64-bit range DAG-CBOR allows",
+        value
+    )
+}
+
+/// Errors raised through `serde::de::Error::custom` become [`Error::Codec`], all others stay
+/// [`Error::Cbor`].
+impl From<serde_cbor::Error> for Error {
+    fn from(error: serde_cbor::Error) -> Self {
+        if error.is_data() {
+            Error::Codec(error.to_string())
+        } else {
+            Error::Cbor(error)
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<CidError> for Error {
+    fn from(error: CidError) -> Self {
+        Error::InvalidCid(error.to_string())
+    }
+}
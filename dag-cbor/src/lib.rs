@@ -1,58 +1,324 @@
+use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::fmt;
-use std::iter::FromIterator;
+use std::io;
 
-use serde::{de, ser, Deserialize, Serialize};
-use serde_bytes;
+use serde::de::DeserializeSeed;
+use serde::{de, ser, Serialize};
 use serde_cbor::tags::{current_cbor_tag, Tagged};
 
-use ipld_core::Ipld;
+use ipld_core::multihash::Code;
+use ipld_core::{Cid, Ipld, MULTIBASE_IDENTITY};
+
+mod block;
+mod borrowed;
+pub mod cid;
+pub mod cid_bytes;
+mod encode;
+mod error;
+mod link;
+pub mod multihash;
+mod validate;
+
+pub use crate::block::Block;
+pub use crate::borrowed::{deserialize_borrowed, from_slice_borrowed};
+pub use crate::error::Error;
+pub use crate::link::{Link, Loader};
+
+/// The multicodec code of DAG-CBOR, the codec of the CIDs [`DagCbor::cid`] returns.
+pub const DAG_CBOR: u64 = 0x71;
 
 const CBOR_TAG_CID: u64 = 42;
+const FLOATS_NOT_ALLOWED: &str = "floats not allowed in strict DAG-CBOR";
+const MISSING_MULTIBASE_PREFIX: &str = "CID is missing the multibase identity prefix (0x00)";
+
+/// The order in which the keys of a map are encoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MapKeyOrder {
+    /// Shorter keys first, keys of the same length bytewise. This is the RFC 7049 canonical
+    /// order the DAG-CBOR spec mandates and what go-ipfs produces.
+    #[default]
+    LengthFirst,
+    /// Plain bytewise order, as used by the RFC 8949 core deterministic encoding and newer
+    /// go-ipld-prime versions.
+    Bytewise,
+}
+
+/// Options for encoding DAG-CBOR.
+#[derive(Clone, Debug, Default)]
+pub struct EncodeOptions {
+    /// The order in which map keys are emitted.
+    pub map_key_order: MapKeyOrder,
+    /// Encode NaN and infinite floats instead of returning an error. The result isn't valid
+    /// DAG-CBOR, only use this for data that knowingly doesn't follow the spec.
+    pub allow_non_finite_floats: bool,
+}
+
+/// Encodes an `Ipld` value as DAG-CBOR.
+///
+/// Integers as well as the lengths of strings, byte strings, lists and maps are always encoded
+/// in their shortest form. Floats are always encoded with 64-bit precision.
+pub fn to_vec(ipld: &Ipld) -> Result<Vec<u8>, Error> {
+    to_vec_with_options(ipld, &EncodeOptions::default())
+}
 
-pub fn encode(ipld: &Ipld) -> Result<Vec<u8>, serde_cbor::Error> {
+/// Encodes an `Ipld` value as DAG-CBOR with the given options.
+pub fn to_vec_with_options(ipld: &Ipld, options: &EncodeOptions) -> Result<Vec<u8>, Error> {
     let mut vec = Vec::new();
-    let mut ser = serde_cbor::Serializer::new(&mut vec);
-    serialize(&ipld, &mut ser)?;
+    encode::write(ipld, options, &mut vec)?;
     Ok(vec)
 }
 
-pub fn decode(data: &[u8]) -> Result<Ipld, serde_cbor::Error> {
-    let mut de = serde_cbor::Deserializer::from_slice(&data);
-    deserialize(&mut de)
+/// Encodes an `Ipld` value as DAG-CBOR into a writer.
+pub fn to_writer<W: io::Write>(ipld: &Ipld, mut writer: W) -> Result<(), Error> {
+    writer.write_all(&to_vec(ipld)?)?;
+    Ok(())
+}
+
+/// Options for decoding DAG-CBOR.
+#[derive(Clone, Debug)]
+pub struct DecodeOptions {
+    /// Reject input that isn't in canonical DAG-CBOR form, e.g. maps whose keys aren't in
+    /// canonical order, integers that aren't encoded in their shortest form or floats with less
+    /// than 64-bit precision. By default such input is accepted, and narrower floats are widened
+    /// to 64-bit.
+    ///
+    /// The checks happen in the validation pass that runs before every decode anyway. They
+    /// compare each header against its shortest form and each map key against the previous
+    /// one, without re-encoding anything, so strict mode costs little on top of it.
+    pub strict: bool,
+    /// Reject all floats, for profiles of DAG-CBOR that don't allow them.
+    pub forbid_floats: bool,
+    /// Decode NaN and infinite floats instead of returning an error. They are never valid
+    /// DAG-CBOR, but may have been written with [`EncodeOptions::allow_non_finite_floats`].
+    pub allow_non_finite_floats: bool,
+    /// How deeply values may be nested. The top-level value is at depth 1, the contents of a
+    /// list, map or tag are one level deeper than the item itself. A list, map or tag at the
+    /// maximum depth is rejected even if it is empty, so at most `max_depth - 1` of them can be
+    /// nested.
+    ///
+    /// The default is 128, like go-ipld-prime. `serde_cbor` has a fixed limit that matches
+    /// the default, so larger values don't allow deeper nesting.
+    pub max_depth: usize,
+    /// How many elements a list and how many entries a map may have.
+    ///
+    /// There is no limit by default. Lengths that can't possibly fit into the rest of the input
+    /// are always rejected, so the memory a decode allocates is bounded by the size of the input
+    /// anyway. This limit is for tighter bounds.
+    pub max_collection_len: usize,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            forbid_floats: false,
+            allow_non_finite_floats: false,
+            max_depth: 128,
+            max_collection_len: usize::MAX,
+        }
+    }
+}
+
+/// Encodes `ipld` and returns the CIDv1 of the encoded block, hashed with SHA2-256.
+///
+/// This is the CID other IPFS implementations assign to the same value by default.
+pub fn cid_of(ipld: &Ipld) -> Result<Cid, Error> {
+    ipld.cid(Code::Sha2_256)
+}
+
+/// Decodes an `Ipld` value from DAG-CBOR.
+///
+/// The input is walked twice: once to validate the raw bytes and once to build the value.
+pub fn from_slice(data: &[u8]) -> Result<Ipld, Error> {
+    from_slice_with_options(data, &DecodeOptions::default())
+}
+
+/// Decodes an `Ipld` value from DAG-CBOR with the given options.
+pub fn from_slice_with_options(data: &[u8], options: &DecodeOptions) -> Result<Ipld, Error> {
+    validate::validate(data, options)?;
+    let mut de = serde_cbor::Deserializer::from_slice(data);
+    Ok(IpldSeed(options).deserialize(&mut de)?)
+}
+
+/// Decodes the first `Ipld` value in `data` and returns it together with the number of bytes it
+/// takes up.
+///
+/// Whatever follows the value is ignored, so this can be used to decode values from a buffer
+/// that holds more data, e.g. a framed protocol.
+pub fn from_slice_tracking(data: &[u8]) -> Result<(Ipld, usize), Error> {
+    let options = DecodeOptions::default();
+    validate::validate(data, &options)?;
+    let mut de = serde_cbor::Deserializer::from_slice(data);
+    let ipld = IpldSeed(&options).deserialize(&mut de)?;
+    Ok((ipld, de.byte_offset()))
+}
+
+/// Returns whether `data` is a single value in canonical DAG-CBOR form.
+///
+/// The value is decoded and encoded again, the result must match `data` byte for byte. Data
+/// that is valid but not canonical, e.g. integers or lengths that aren't encoded in their
+/// shortest form, maps whose keys aren't in canonical order, indefinite-length items, floats
+/// with less than 64-bit precision or trailing bytes, returns `false`. Data that can't be
+/// decoded at all returns an error.
+pub fn is_canonical(data: &[u8]) -> Result<bool, Error> {
+    let (ipld, len) = match from_slice_tracking(data) {
+        Ok(decoded) => decoded,
+        Err(Error::IndefiniteLength { .. }) => return Ok(false),
+        Err(error) => return Err(error),
+    };
+    Ok(len == data.len() && to_vec(&ipld)? == data)
+}
+
+/// Decodes a value and encodes it again in canonical DAG-CBOR form.
+///
+/// Everything the default [`DecodeOptions`] accept is repaired, e.g. integers that aren't
+/// encoded in their shortest form, misordered map keys and floats with less than 64-bit
+/// precision. Things that are invalid rather than just not canonical, like NaN, duplicate map
+/// keys or indefinite-length items, are still errors. The result passes [`is_canonical`].
+pub fn recanonicalize(data: &[u8]) -> Result<Vec<u8>, Error> {
+    to_vec(&from_slice(data)?)
+}
+
+/// Decodes an `Ipld` value from a reader that contains nothing but DAG-CBOR.
+pub fn from_reader<R: io::Read>(mut reader: R) -> Result<Ipld, Error> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    from_slice(&data)
+}
+
+/// Decodes a CBOR sequence, i.e. DAG-CBOR items that directly follow each other.
+///
+/// The items are decoded one at a time. Decoding stops at the end of the input or at the first
+/// error, e.g. if the last item is truncated. Error offsets are relative to the start of
+/// `data`.
+pub fn iter_from_slice(data: &[u8]) -> impl Iterator<Item = Result<Ipld, Error>> + '_ {
+    SequenceIter {
+        data,
+        de: serde_cbor::Deserializer::from_slice(data),
+        options: DecodeOptions::default(),
+        done: false,
+    }
+}
+
+struct SequenceIter<'a> {
+    data: &'a [u8],
+    de: serde_cbor::Deserializer<serde_cbor::de::SliceRead<'a>>,
+    options: DecodeOptions,
+    done: bool,
+}
+
+impl<'a> Iterator for SequenceIter<'a> {
+    type Item = Result<Ipld, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.de.byte_offset();
+        if self.done || offset == self.data.len() {
+            return None;
+        }
+        let result = validate::validate_at(self.data, offset, &self.options)
+            .and_then(|_| Ok(IpldSeed(&self.options).deserialize(&mut self.de)?));
+        self.done = result.is_err();
+        Some(result)
+    }
+}
+
+/// Same as [`to_vec`].
+pub fn encode(ipld: &Ipld) -> Result<Vec<u8>, Error> {
+    to_vec(ipld)
+}
+
+/// Same as [`from_slice`].
+pub fn decode(data: &[u8]) -> Result<Ipld, Error> {
+    from_slice(data)
+}
+
+/// Methods for encoding to and decoding from DAG-CBOR.
+///
+/// They aren't inherent methods for the reason given in the [`cid`] module. Bring the trait
+/// into scope to write `Ipld::from_bytes(&buf)`.
+pub trait DagCbor: Sized {
+    /// Encodes the value as DAG-CBOR.
+    fn to_bytes(&self) -> Result<Vec<u8>, Error>;
+    /// Decodes a value from DAG-CBOR.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Error>;
+
+    /// Returns the number of bytes [`DagCbor::to_bytes`] produces, or the error it returns.
+    ///
+    /// `Ipld` and `Cid` compute it without encoding anything. The default implementation encodes
+    /// the value.
+    fn byte_len(&self) -> Result<usize, Error> {
+        Ok(self.to_bytes()?.len())
+    }
+
+    /// Encodes the value and returns the CIDv1 of the encoded block, hashed with `hash`.
+    fn cid(&self, hash: Code) -> Result<Cid, Error> {
+        Ok(Cid::new_v1(DAG_CBOR, hash.digest(&self.to_bytes()?)))
+    }
+}
+
+impl DagCbor for Ipld {
+    fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        to_vec(self)
+    }
+
+    fn byte_len(&self) -> Result<usize, Error> {
+        encode::len(self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        from_slice(bytes)
+    }
+}
+
+/// A `Cid` is encoded as a single link.
+impl DagCbor for Cid {
+    fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        to_vec(&Ipld::Link(self.clone()))
+    }
+
+    fn byte_len(&self) -> Result<usize, Error> {
+        Ok(encode::link_len(self))
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        match from_slice(bytes)? {
+            Ipld::Link(cid) => Ok(cid),
+            _ => Err(Error::Codec("link expected".to_string())),
+        }
+    }
 }
 
 // Needed for `visit_seq` and `visit_map` in Deserializer
-/// We cannot directly implement `serde::Deserializer` for `Ipld` as it is a remote type.
-/// Instead wrap it into a newtype struct and implement `serde::Deserialize` for that one.
-/// All the deserializer does is calling the `deserialize()` function we defined which returns
-/// an unwrapped `Ipld` instance. Wrap that `Ipld` instance in `Wrapper` and return it.
-/// Users of this wrapper will then unwrap it again so that they can return the expected `Ipld`
-/// instance.
-struct WrapperOwned(Ipld);
-impl<'de> Deserialize<'de> for WrapperOwned {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+/// We cannot directly implement `serde::Deserialize` for `Ipld` as it is a remote type.
+/// Nested values are deserialized through this seed instead, which also carries the decode
+/// options down to every level.
+#[derive(Clone, Copy)]
+struct IpldSeed<'a>(&'a DecodeOptions);
+impl<'de, 'a> de::DeserializeSeed<'de> for IpldSeed<'a> {
+    type Value = Ipld;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: de::Deserializer<'de>,
     {
-        let deserialized = deserialize(deserializer);
-        // Better version of Ok(Wrapper(deserialized.unwrap()))
-        deserialized.map(Self)
+        deserializer.deserialize_any(IpldCborVisitor(self.0))
     }
 }
 // Needed for `collect_seq` and `collect_map` in Deserializer
-struct Wrapper<'a>(&'a Ipld);
+struct Wrapper<'a>(&'a Ipld, &'a EncodeOptions);
 impl<'a> Serialize for Wrapper<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: ser::Serializer,
     {
-        serialize(&self.0, serializer)
+        serialize_with_options(self.0, serializer, self.1)
     }
 }
 
-struct IpldCborVisitor;
-impl<'de> de::Visitor<'de> for IpldCborVisitor {
+struct IpldCborVisitor<'a>(&'a DecodeOptions);
+impl<'de, 'a> de::Visitor<'de> for IpldCborVisitor<'a> {
     type Value = Ipld;
 
     fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -111,6 +377,9 @@ impl<'de> de::Visitor<'de> for IpldCborVisitor {
     where
         E: de::Error,
     {
+        if !encode::is_in_range(v) {
+            return Err(de::Error::custom(error::integer_out_of_range(v)));
+        }
         Ok(Ipld::Integer(v))
     }
 
@@ -143,14 +412,13 @@ impl<'de> de::Visitor<'de> for IpldCborVisitor {
     where
         V: de::SeqAccess<'de>,
     {
-        let mut vec: Vec<WrapperOwned> = Vec::new();
+        let mut vec = Vec::new();
 
-        while let Some(elem) = visitor.next_element()? {
+        while let Some(elem) = visitor.next_element_seed(IpldSeed(self.0))? {
             vec.push(elem);
         }
 
-        let unwrapped = vec.into_iter().map(|WrapperOwned(ipld)| ipld).collect();
-        Ok(Ipld::List(unwrapped))
+        Ok(Ipld::List(vec))
     }
 
     #[inline]
@@ -158,16 +426,50 @@ impl<'de> de::Visitor<'de> for IpldCborVisitor {
     where
         V: de::MapAccess<'de>,
     {
-        let mut values: Vec<(String, WrapperOwned)> = Vec::new();
+        let mut values: Vec<(String, Ipld)> = Vec::new();
 
-        while let Some((key, value)) = visitor.next_entry()? {
-            values.push((key, value));
+        while let Some(key) = visitor.next_key_seed(IpldSeed(self.0))? {
+            let key = match key {
+                Ipld::String(key) => key,
+                _ => return Err(de::Error::custom("map keys must be strings")),
+            };
+            if self.0.strict {
+                if let Some((previous, _)) = values.last() {
+                    if !is_canonical_key_order(previous.as_bytes(), key.as_bytes()) {
+                        return Err(de::Error::custom(format!(
+                            "map key {:?} is not in canonical order",
+                            key
+                        )));
+                    }
+                }
+            }
+            values.push((key, visitor.next_value_seed(IpldSeed(self.0))?));
         }
 
-        let unwrapped = values
-            .into_iter()
-            .map(|(key, WrapperOwned(value))| (key, value));
-        Ok(Ipld::Map(BTreeMap::from_iter(unwrapped)))
+        let mut map = BTreeMap::new();
+        for (key, value) in values {
+            match map.entry(key) {
+                Entry::Occupied(entry) => {
+                    return Err(de::Error::custom(format!(
+                        "duplicate map key {:?}",
+                        entry.key()
+                    )))
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+            }
+        }
+        Ok(Ipld::Map(map))
+    }
+
+    #[inline]
+    fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        // Half-precision floats arrive here as well.
+        self.visit_f64(f64::from(v))
     }
 
     #[inline]
@@ -175,6 +477,15 @@ impl<'de> de::Visitor<'de> for IpldCborVisitor {
     where
         E: de::Error,
     {
+        if self.0.forbid_floats {
+            return Err(de::Error::custom(FLOATS_NOT_ALLOWED));
+        }
+        if !v.is_finite() && !self.0.allow_non_finite_floats {
+            return Err(de::Error::custom(format!(
+                "{} is not allowed in DAG-CBOR",
+                v
+            )));
+        }
         Ok(Ipld::Float(v))
     }
 
@@ -185,47 +496,111 @@ impl<'de> de::Visitor<'de> for IpldCborVisitor {
     {
         match current_cbor_tag() {
             Some(CBOR_TAG_CID) => {
-                let link = match deserialize(deserializer) {
+                let mut link = match IpldSeed(self.0).deserialize(deserializer) {
                     Ok(Ipld::Bytes(link)) => link,
                     _ => return Err(de::Error::custom("bytes expected")),
                 };
-                Ok(Ipld::Link(link))
+                strip_multibase_prefix(&mut link)?;
+                Cid::try_from(link)
+                    .map(Ipld::Link)
+                    .map_err(de::Error::custom)
             }
-            Some(tag) => Err(de::Error::custom(format!("unexpected tag ({})", tag))),
+            Some(tag) => Err(unexpected_tag(tag)),
             _ => Err(de::Error::custom("tag expected")),
         }
     }
 }
 
-fn serialize<S>(ipld: &Ipld, ser: S) -> Result<S::Ok, S::Error>
+/// The error for any tag other than 42, it matches the one of [`Error::UnexpectedTag`].
+fn unexpected_tag<E: de::Error>(tag: u64) -> E {
+    de::Error::custom(format!("unexpected CBOR tag {} in DAG-CBOR", tag))
+}
+
+/// Serializes an `Ipld` value with any Serde serializer.
+///
+/// It stands in for a `Serialize` implementation, see the [`cid`] module. Together with
+/// [`deserialize`] this function can be used as `#[serde(with = "ipld_dag_cbor")]` on fields of
+/// type `Ipld`. Byte strings are emitted as CBOR byte strings and links as tag 42. Map keys are
+/// emitted in the canonical DAG-CBOR order. How floats are encoded is up to the serializer,
+/// `serde_cbor` for example shrinks them if that is lossless. Use [`to_vec`] to get canonical
+/// DAG-CBOR.
+pub fn serialize<S>(ipld: &Ipld, ser: S) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+{
+    serialize_with_options(ipld, ser, &EncodeOptions::default())
+}
+
+fn serialize_with_options<S>(
+    ipld: &Ipld,
+    ser: S,
+    options: &EncodeOptions,
+) -> Result<S::Ok, S::Error>
 where
     S: ser::Serializer,
 {
     match &ipld {
         Ipld::Null => ser.serialize_none(),
         Ipld::Bool(bool) => ser.serialize_bool(*bool),
-        Ipld::Integer(i128) => ser.serialize_i128(*i128),
-        Ipld::Float(f64) => ser.serialize_f64(*f64),
-        Ipld::String(string) => ser.serialize_str(&string),
-        Ipld::Bytes(bytes) => ser.serialize_bytes(&bytes),
+        Ipld::Integer(i128) => {
+            if !encode::is_in_range(*i128) {
+                return Err(ser::Error::custom(error::integer_out_of_range(*i128)));
+            }
+            ser.serialize_i128(*i128)
+        }
+        Ipld::Float(f64) => {
+            if !f64.is_finite() && !options.allow_non_finite_floats {
+                return Err(ser::Error::custom(format!(
+                    "{} is not allowed in DAG-CBOR",
+                    f64
+                )));
+            }
+            ser.serialize_f64(*f64)
+        }
+        Ipld::String(string) => ser.serialize_str(string),
+        Ipld::Bytes(bytes) => ser.serialize_bytes(bytes),
         Ipld::List(list) => {
-            let wrapped = list.iter().map(|ipld| Wrapper(ipld));
+            let wrapped = list.iter().map(|ipld| Wrapper(ipld, options));
             ser.collect_seq(wrapped)
         }
         Ipld::Map(map) => {
-            let wrapped = map.iter().map(|(key, ipld)| (key, Wrapper(ipld)));
+            let wrapped = encode::sorted_entries(map, options.map_key_order)
+                .into_iter()
+                .map(|(key, ipld)| (key, Wrapper(ipld, options)));
             ser.collect_map(wrapped)
         }
-        Ipld::Link(link) => {
-            let value = serde_bytes::Bytes::new(&link);
+        Ipld::Link(cid) => {
+            let bytes = cid.link_bytes();
+            let value = serde_bytes::Bytes::new(&bytes);
             Tagged::new(Some(CBOR_TAG_CID), &value).serialize(ser)
         }
     }
 }
 
-fn deserialize<'de, D>(deserializer: D) -> Result<Ipld, D::Error>
+/// Removes the multibase prefix from the contents of tag 42.
+fn strip_multibase_prefix<E: de::Error>(bytes: &mut Vec<u8>) -> Result<(), E> {
+    match bytes.first() {
+        Some(&MULTIBASE_IDENTITY) => {
+            bytes.remove(0);
+            Ok(())
+        }
+        _ => Err(de::Error::custom(MISSING_MULTIBASE_PREFIX)),
+    }
+}
+
+/// Deserializes an `Ipld` value from any Serde deserializer.
+///
+/// This is the counterpart of [`serialize`]. Checks that need the raw bytes are only done by
+/// [`from_slice`], e.g. `serde_cbor` reports `undefined` just like `null`, so it ends up as
+/// `Ipld::Null` here, and indefinite-length items can't be told apart from definite ones.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Ipld, D::Error>
 where
     D: de::Deserializer<'de>,
 {
-    deserializer.deserialize_any(IpldCborVisitor)
+    de::DeserializeSeed::deserialize(IpldSeed(&DecodeOptions::default()), deserializer)
+}
+
+/// Returns whether `second` may follow `first` in a canonically ordered map.
+fn is_canonical_key_order(first: &[u8], second: &[u8]) -> bool {
+    (first.len(), first) < (second.len(), second)
 }
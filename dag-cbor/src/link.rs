@@ -0,0 +1,107 @@
+//! Links that know the type of the block they point to.
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use serde::de::DeserializeOwned;
+use serde::{de, ser, Deserialize, Serialize};
+
+use ipld_core::Cid;
+
+use crate::{validate, DecodeOptions, Error};
+
+/// Returns blocks by their CID, e.g. from a block store.
+pub trait Loader {
+    /// Returns the encoded block with the given CID.
+    fn load(&self, cid: &Cid) -> Result<Vec<u8>, Error>;
+}
+
+/// A CID of a DAG-CBOR block that decodes into a `T`.
+///
+/// It is encoded as a plain link, the type only exists on the Rust side. Unlike a `Cid` it can
+/// be a field of a struct that derives `Serialize` and `Deserialize` without a `with`
+/// attribute.
+pub struct Link<T>(Cid, PhantomData<T>);
+
+impl<T> Link<T> {
+    /// Creates a link to a block that decodes into a `T`.
+    pub fn new(cid: Cid) -> Self {
+        Self(cid, PhantomData)
+    }
+
+    /// Returns the CID and drops the type.
+    pub fn into_cid(self) -> Cid {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned> Link<T> {
+    /// Loads the block from `loader` and decodes it.
+    ///
+    /// The block is trusted to be the one the CID points to, its hash isn't checked.
+    pub fn load<L: Loader + ?Sized>(&self, loader: &L) -> Result<T, Error> {
+        let block = loader.load(&self.0)?;
+        validate::validate(&block, &DecodeOptions::default())?;
+        Ok(serde_cbor::from_slice(&block)?)
+    }
+}
+
+impl<T> From<Cid> for Link<T> {
+    fn from(cid: Cid) -> Self {
+        Self::new(cid)
+    }
+}
+
+impl<T> Deref for Link<T> {
+    type Target = Cid;
+
+    fn deref(&self) -> &Cid {
+        &self.0
+    }
+}
+
+// The traits are implemented by hand, deriving them would require them of `T` as well.
+impl<T> Clone for Link<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.0.clone())
+    }
+}
+
+impl<T> PartialEq for Link<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T> Eq for Link<T> {}
+
+impl<T> Hash for Link<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(&self.0, state)
+    }
+}
+
+impl<T> fmt::Debug for Link<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "Link({})", self.0)
+    }
+}
+
+impl<T> Serialize for Link<T> {
+    fn serialize<S>(&self, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        crate::cid::serialize(&self.0, ser)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Link<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        crate::cid::deserialize(deserializer).map(Self::new)
+    }
+}
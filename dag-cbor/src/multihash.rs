@@ -0,0 +1,30 @@
+//! Computing multihashes by their code, e.g. the one of an existing CID.
+use ipld_core::multihash::Code;
+use ipld_core::Multihash;
+
+use crate::Error;
+
+/// A hash function that blocks can be hashed with, see [`Block::encode_with_hash`].
+///
+/// It is implemented for [`Code`], implement it to use other hash functions.
+///
+/// [`Block::encode_with_hash`]: crate::Block::encode_with_hash
+pub trait Hasher {
+    /// Hashes `data`.
+    fn digest(&self, data: &[u8]) -> Multihash;
+}
+
+impl Hasher for Code {
+    fn digest(&self, data: &[u8]) -> Multihash {
+        Code::digest(*self, data)
+    }
+}
+
+/// Hashes `data` with the hash function of the multihash `code` and returns the binary
+/// multihash: the code and the length of the digest as varints, followed by the digest.
+///
+/// The supported hash functions are the ones of [`Code`].
+pub fn digest(code: u64, data: &[u8]) -> Result<Vec<u8>, Error> {
+    let hash = Code::from_code(code).ok_or(Error::UnsupportedHash { code })?;
+    Ok(hash.digest(data).to_bytes())
+}
@@ -0,0 +1,324 @@
+//! Checks raw DAG-CBOR bytes for things Serde can't see or report in a structured way.
+//!
+//! The validator walks the encoded item without building any values. Input that isn't
+//! well-formed CBOR is left to `serde_cbor`, which reports it with its usual syntax errors.
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+
+use ipld_core::{Cid, MULTIBASE_IDENTITY};
+
+use crate::{encode, is_canonical_key_order, DecodeOptions, Error, CBOR_TAG_CID};
+
+/// Why validation stopped early.
+enum Stop {
+    /// The input isn't well-formed CBOR.
+    Malformed,
+    /// The input is well-formed CBOR, but not valid DAG-CBOR.
+    Invalid(Error),
+}
+
+impl From<Error> for Stop {
+    fn from(error: Error) -> Self {
+        Stop::Invalid(error)
+    }
+}
+
+type Result<T> = std::result::Result<T, Stop>;
+
+/// Validates the first item in `data`.
+pub(crate) fn validate(data: &[u8], options: &DecodeOptions) -> std::result::Result<(), Error> {
+    validate_at(data, 0, options)
+}
+
+/// Validates the item that starts at `offset`, errors report offsets into all of `data`.
+pub(crate) fn validate_at(
+    data: &[u8],
+    offset: usize,
+    options: &DecodeOptions,
+) -> std::result::Result<(), Error> {
+    let mut validator = Validator {
+        data,
+        offset,
+        depth: 1,
+        options,
+    };
+    match validator.item() {
+        Ok(_) | Err(Stop::Malformed) => Ok(()),
+        Err(Stop::Invalid(error)) => Err(error),
+    }
+}
+
+struct Validator<'a> {
+    data: &'a [u8],
+    offset: usize,
+    /// The depth of the item that is read next.
+    depth: usize,
+    options: &'a DecodeOptions,
+}
+
+impl<'a> Validator<'a> {
+    fn read(&mut self, len: u64) -> Result<&'a [u8]> {
+        let remaining = self.data.len() - self.offset;
+        if len > remaining as u64 {
+            return Err(Stop::Malformed);
+        }
+        let bytes = &self.data[self.offset..self.offset + len as usize];
+        self.offset += len as usize;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read(1)?[0])
+    }
+
+    /// Reads the argument that follows an initial byte with the given additional info.
+    fn argument(&mut self, info: u8) -> Result<u64> {
+        let width = match info {
+            0..=23 => return Ok(u64::from(info)),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            _ => return Err(Stop::Malformed),
+        };
+        let bytes = self.read(width)?;
+        Ok(bytes
+            .iter()
+            .fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+    }
+
+    /// Reads the integer, length or tag number of the item whose initial byte is at `start`.
+    ///
+    /// In strict mode the argument must be encoded in its shortest form.
+    fn header(&mut self, start: usize, info: u8) -> Result<u64> {
+        let value = self.argument(info)?;
+        let minimal = match info {
+            24 => value >= 24,
+            25 => value > 0xff,
+            26 => value > 0xffff,
+            27 => value > 0xffff_ffff,
+            _ => true,
+        };
+        if self.options.strict && !minimal {
+            return Err(Error::NonMinimalEncoding {
+                offset: start,
+                value,
+            }
+            .into());
+        }
+        Ok(value)
+    }
+
+    /// Reads the length of a string or collection whose initial byte is at `start`.
+    ///
+    /// Each unit of the length needs at least `min_size` bytes of input. Lengths that can't fit
+    /// into the remaining input are rejected, so that nothing allocates memory based on them.
+    fn length(&mut self, start: usize, info: u8, min_size: u64) -> Result<u64> {
+        let length = match info {
+            31 => return Err(Error::IndefiniteLength { offset: start }.into()),
+            _ => self.header(start, info)?,
+        };
+        let remaining = (self.data.len() - self.offset) as u64;
+        if length.saturating_mul(min_size) > remaining {
+            return Err(Error::LengthExceedsInput {
+                length,
+                offset: start,
+            }
+            .into());
+        }
+        Ok(length)
+    }
+
+    /// Reads the number of elements or entries of a list or map whose initial byte is at
+    /// `start`.
+    fn collection_length(&mut self, start: usize, info: u8, min_size: u64) -> Result<u64> {
+        let length = self.length(start, info, min_size)?;
+        if length > self.options.max_collection_len as u64 {
+            return Err(Error::CollectionTooLong {
+                length,
+                limit: self.options.max_collection_len,
+                offset: start,
+            }
+            .into());
+        }
+        Ok(length)
+    }
+
+    /// Validates the contents of the list, map or tag at `start` one level deeper.
+    fn nested<F>(&mut self, start: usize, contents: F) -> Result<()>
+    where
+        F: FnOnce(&mut Self) -> Result<()>,
+    {
+        if self.depth >= self.options.max_depth {
+            return Err(Error::MaxDepthExceeded {
+                limit: self.options.max_depth,
+                offset: start,
+            }
+            .into());
+        }
+        self.depth += 1;
+        contents(self)?;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    /// Checks that the contents of a link, the item from `start` up to the current offset, are
+    /// a valid CID. Contents that aren't a byte string with the identity prefix are reported
+    /// while decoding.
+    fn link(&self, start: usize) -> Result<()> {
+        let byte = self.data[start];
+        let header_len = match byte & 0x1f {
+            0..=23 => 1,
+            24 => 2,
+            25 => 3,
+            26 => 5,
+            _ => 9,
+        };
+        match &self.data[start + header_len..self.offset] {
+            [MULTIBASE_IDENTITY, cid @ ..] if byte >> 5 == 2 => match Cid::try_from(cid) {
+                Ok(_) => Ok(()),
+                Err(error) => Err(Error::from(error).into()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Validates a single item and returns the contents if it is a text string.
+    fn item(&mut self) -> Result<Option<&'a [u8]>> {
+        let start = self.offset;
+        let byte = self.read_u8()?;
+        let (major, info) = (byte >> 5, byte & 0x1f);
+        match major {
+            0 => {
+                self.header(start, info)?;
+            }
+            1 => {
+                // Negative integers `n` are encoded as `-1 - n`.
+                let value = -1 - i128::from(self.header(start, info)?);
+                if !encode::is_in_range(value) {
+                    return Err(Error::IntegerOutOfRange {
+                        value,
+                        offset: Some(start),
+                    }
+                    .into());
+                }
+            }
+            2 => {
+                let len = self.length(start, info, 1)?;
+                self.read(len)?;
+            }
+            3 => {
+                let len = self.length(start, info, 1)?;
+                return self.read(len).map(Some);
+            }
+            4 => {
+                let len = self.collection_length(start, info, 1)?;
+                self.nested(start, |validator| {
+                    for _ in 0..len {
+                        validator.item()?;
+                    }
+                    Ok(())
+                })?;
+            }
+            5 => {
+                let len = self.collection_length(start, info, 2)?;
+                self.nested(start, |validator| validator.map(len))?;
+            }
+            6 => {
+                let tag = self.header(start, info)?;
+                if tag != CBOR_TAG_CID {
+                    return Err(Error::UnexpectedTag { tag, offset: start }.into());
+                }
+                self.nested(start, |validator| {
+                    let contents = validator.offset;
+                    validator.item()?;
+                    validator.link(contents)
+                })?;
+            }
+            _ => {
+                // Simple values and floats.
+                let bits = self.argument(info)?;
+                // Only `false`, `true` and `null` are part of the data model.
+                if info < 20 || info == 23 || info == 24 {
+                    return Err(Error::UnsupportedSimpleValue {
+                        value: bits as u8,
+                        offset: start,
+                    }
+                    .into());
+                }
+                if self.options.forbid_floats && (25..=27).contains(&info) {
+                    return Err(Error::FloatNotAllowed { offset: start }.into());
+                }
+                let value = match info {
+                    25 => non_finite(bits, 10, 5),
+                    26 => non_finite(bits, 23, 8),
+                    27 => non_finite(bits, 52, 11),
+                    _ => None,
+                };
+                if let Some(value) = value {
+                    if !self.options.allow_non_finite_floats {
+                        return Err(Error::InvalidFloat {
+                            value,
+                            offset: Some(start),
+                        }
+                        .into());
+                    }
+                }
+                if self.options.strict && (info == 25 || info == 26) {
+                    return Err(Error::NonCanonicalFloat {
+                        bits: if info == 25 { 16 } else { 32 },
+                        offset: start,
+                    }
+                    .into());
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn map(&mut self, len: u64) -> Result<()> {
+        let mut keys = BTreeSet::new();
+        let mut previous = None;
+        for _ in 0..len {
+            let key_offset = self.offset;
+            let key = self
+                .item()?
+                .ok_or(Error::NonStringKey { offset: key_offset })?;
+            if !keys.insert(key) {
+                return Err(Error::DuplicateKey {
+                    key: String::from_utf8_lossy(key).into_owned(),
+                    offset: key_offset,
+                }
+                .into());
+            }
+            if self.options.strict {
+                if let Some(previous) = previous {
+                    if !is_canonical_key_order(previous, key) {
+                        return Err(Error::UnorderedKey {
+                            key: String::from_utf8_lossy(key).into_owned(),
+                            offset: key_offset,
+                        }
+                        .into());
+                    }
+                }
+            }
+            previous = Some(key);
+            self.item()?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the value of a float with the given layout if it is NaN or infinite.
+fn non_finite(bits: u64, mantissa_bits: u32, exponent_bits: u32) -> Option<f64> {
+    let exponent = (bits >> mantissa_bits) & ((1 << exponent_bits) - 1);
+    if exponent != (1 << exponent_bits) - 1 {
+        return None;
+    }
+    let negative = bits >> (mantissa_bits + exponent_bits) & 1 == 1;
+    let value = match bits & ((1 << mantissa_bits) - 1) {
+        0 => f64::INFINITY,
+        _ => f64::NAN,
+    };
+    Some(if negative { -value } else { value })
+}
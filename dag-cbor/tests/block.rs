@@ -0,0 +1,120 @@
+use std::str::FromStr;
+
+use ipld_core::multihash::Code;
+use ipld_core::{ipld, Cid, Ipld, Multihash};
+use ipld_dag_cbor::{multihash, Block, Error, DAG_CBOR};
+
+#[test]
+fn encode_block_like_go_ipfs() {
+    // The CIDs `ipfs dag put` assigns to these values.
+    let fixtures = vec![
+        (
+            ipld!({ "hello": "world" }),
+            "bafyreidykglsfhoixmivffc5uwhcgshx4j465xwqntbmu43nb2dzqwfvae",
+        ),
+        (
+            ipld!({}),
+            "bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua",
+        ),
+    ];
+    for (ipld, cid) in fixtures {
+        let block = Block::encode(&ipld).unwrap();
+        assert_eq!(block.cid().to_string(), cid);
+        assert_eq!(block.data(), &ipld_dag_cbor::to_vec(&ipld).unwrap()[..]);
+        assert_eq!(block.decode::<Ipld>().unwrap(), ipld);
+        let (cid, data) = block.clone().into_parts();
+        assert_eq!(Block::new(cid, data).unwrap(), block);
+    }
+}
+
+#[test]
+fn encode_block_with_hash() {
+    let block = Block::encode_with_hash(&ipld!([1, 2]), Code::Sha2_512).unwrap();
+    assert_eq!(block.cid().codec(), DAG_CBOR);
+    assert_eq!(block.cid().hash().code(), 0x13);
+    assert_eq!(block.cid().hash().digest().len(), 64);
+    assert!(Block::new(block.cid().clone(), block.data().to_vec()).is_ok());
+}
+
+/// Hashes with a made-up hash function: the XOR of all bytes.
+struct Xor;
+
+impl multihash::Hasher for Xor {
+    fn digest(&self, data: &[u8]) -> Multihash {
+        Multihash::new(0x300000, vec![data.iter().fold(0, |xor, byte| xor ^ byte)])
+    }
+}
+
+#[test]
+fn encode_block_with_custom_hasher() {
+    let block = Block::encode_with_hash(&ipld!([1, 2]), Xor).unwrap();
+    // `[1, 2]` is encoded as `0x82 0x01 0x02`.
+    assert_eq!(block.cid().hash(), &Multihash::new(0x300000, vec![0x81]));
+    let (cid, data) = block.clone().into_parts();
+    assert_eq!(
+        Block::new_with_hasher(cid.clone(), data.clone(), Xor).unwrap(),
+        block
+    );
+    assert!(Block::new_with_hasher(cid.clone(), vec![0x80], Xor).is_err());
+    assert!(Block::new(cid, data).is_err());
+}
+
+#[test]
+fn new_block_checks_hash() {
+    let cid = Cid::from_str("bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua").unwrap();
+    assert!(Block::new(cid.clone(), vec![0xa0]).is_ok());
+    match Block::new(cid.clone(), vec![0xf6]).unwrap_err() {
+        Error::HashMismatch { cid: expected } => assert_eq!(expected, cid),
+        error => panic!("unexpected error: {}", error),
+    }
+
+    let blake3 = Cid::new_v1(DAG_CBOR, Multihash::new(0x1e, vec![0; 32]));
+    assert_eq!(
+        Block::new(blake3, vec![0xa0]).unwrap_err().to_string(),
+        "unsupported multihash code 0x1e"
+    );
+}
+
+#[test]
+fn multihash_test_vectors() {
+    let sha2_256 = [
+        &[0x12, 0x20][..],
+        &[
+            0x2c, 0x26, 0xb4, 0x6b, 0x68, 0xff, 0xc6, 0x8f, 0xf9, 0x9b, 0x45, 0x3c, 0x1d, 0x30,
+            0x41, 0x34, 0x13, 0x42, 0x2d, 0x70, 0x64, 0x83, 0xbf, 0xa0, 0xf9, 0x8a, 0x5e, 0x88,
+            0x62, 0x66, 0xe7, 0xae,
+        ],
+    ]
+    .concat();
+    assert_eq!(multihash::digest(0x12, b"foo").unwrap(), sha2_256);
+    let sha2_512 = multihash::digest(0x13, b"foo").unwrap();
+    assert_eq!(sha2_512[..6], [0x13, 0x40, 0xf7, 0xfb, 0xba, 0x6e]);
+    assert_eq!(sha2_512.len(), 66);
+    assert_eq!(
+        multihash::digest(0x00, b"foo").unwrap(),
+        vec![0x00, 0x03, b'f', b'o', b'o']
+    );
+    // Lengths and codes above 127 take more than one varint byte.
+    let long = multihash::digest(0x00, &[7; 200]).unwrap();
+    assert_eq!(long[..3], [0x00, 0xc8, 0x01]);
+    assert_eq!(long.len(), 203);
+    assert_eq!(
+        Multihash::new(0xb220, vec![1]).to_bytes(),
+        vec![0xa0, 0xe4, 0x02, 0x01, 0x01]
+    );
+    assert!(matches!(
+        multihash::digest(0xb220, b"foo").unwrap_err(),
+        Error::UnsupportedHash { code: 0xb220 }
+    ));
+}
+
+#[test]
+fn cid_of_value() {
+    let ipld = ipld!({ "hello": "world" });
+    let cid = ipld_dag_cbor::cid_of(&ipld).unwrap();
+    assert_eq!(
+        cid.to_string(),
+        "bafyreidykglsfhoixmivffc5uwhcgshx4j465xwqntbmu43nb2dzqwfvae"
+    );
+    assert_eq!(&cid, Block::encode(&ipld).unwrap().cid());
+}
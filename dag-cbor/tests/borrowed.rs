@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use std::convert::TryFrom;
+
+use ipld_core::{Cid, Ipld, IpldRef};
+use ipld_dag_cbor::{from_slice_borrowed, to_vec};
+
+/// Returns whether `inner` points into `outer`.
+fn points_into(inner: &[u8], outer: &[u8]) -> bool {
+    let range = outer.as_ptr_range();
+    range.start <= inner.as_ptr() && inner.as_ptr_range().end <= range.end
+}
+
+#[test]
+fn decode_large_blob_without_copying() {
+    let blob: Vec<u8> = (0..1 << 20).map(|i| i as u8).collect();
+    let mut map = BTreeMap::new();
+    map.insert("blob".to_string(), Ipld::Bytes(blob.clone()));
+    map.insert("name".to_string(), Ipld::String("x".repeat(1000)));
+    let cid = Cid::try_from(&[1, 0x71, 0, 0][..]).unwrap();
+    map.insert("link".to_string(), Ipld::Link(cid.clone()));
+    let encoded = to_vec(&Ipld::Map(map.clone())).unwrap();
+
+    let decoded = from_slice_borrowed(&encoded).unwrap();
+    let map_ref = match &decoded {
+        IpldRef::Map(map) => map,
+        _ => panic!("map expected"),
+    };
+    match map_ref["blob"] {
+        IpldRef::Bytes(bytes) => {
+            assert_eq!(bytes, &blob[..]);
+            assert!(points_into(bytes, &encoded));
+            // The map header, the key "blob" and the 5 byte header of the byte string.
+            assert_eq!(bytes.as_ptr(), encoded[1 + 5 + 5..].as_ptr());
+        }
+        _ => panic!("bytes expected"),
+    }
+    match map_ref["name"] {
+        IpldRef::String(string) => assert!(points_into(string.as_bytes(), &encoded)),
+        _ => panic!("string expected"),
+    }
+    assert_eq!(map_ref["link"], IpldRef::Link(cid));
+    assert_eq!(decoded.to_owned(), Ipld::Map(map));
+}
+
+#[test]
+fn decode_borrowed_validates() {
+    assert!(matches!(
+        from_slice_borrowed(&[0xa2, 0x61, 0x61, 0x01, 0x61, 0x61, 0x02]).unwrap_err(),
+        ipld_dag_cbor::Error::DuplicateKey { .. }
+    ));
+}
+
+#[test]
+fn deserialize_borrowed_from_reader_fails() {
+    let encoded = to_vec(&Ipld::String("borrowed".to_string())).unwrap();
+    let mut de = serde_cbor::Deserializer::from_reader(&encoded[..]);
+    assert!(ipld_dag_cbor::deserialize_borrowed(&mut de).is_err());
+}
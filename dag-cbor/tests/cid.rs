@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use ipld_core::multihash::{self, Code};
+use ipld_core::{ipld, Cid, Ipld, Version, DAG_PB};
+use ipld_dag_cbor::DagCbor;
+use serde::{Deserialize, Serialize};
+
+/// `bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi`
+const CID_V1_DAG_PB: [u8; 36] = [
+    0x01, 0x70, 0x12, 0x20, 0xc3, 0xc4, 0x73, 0x3e, 0xc8, 0xaf, 0xfd, 0x06, 0xcf, 0x9e, 0x9f, 0xf5,
+    0x0f, 0xfc, 0x6b, 0xcd, 0x2e, 0xc8, 0x5a, 0x61, 0x70, 0x00, 0x4b, 0xb7, 0x09, 0x66, 0x9c, 0x31,
+    0xde, 0x94, 0x39, 0x1a,
+];
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Contact {
+    name: String,
+    #[serde(with = "ipld_dag_cbor::cid")]
+    details: Cid,
+}
+
+#[test]
+fn roundtrip_cid_field() {
+    let contact = Contact {
+        name: "Hello World!".to_string(),
+        details: Cid::try_from(&CID_V1_DAG_PB[..]).unwrap(),
+    };
+    let encoded = serde_cbor::to_vec(&contact).unwrap();
+    // Tag 42 followed by a byte string of 37 bytes, the identity prefix and the CID.
+    let expected_link = [&[0xd8, 0x2a, 0x58, 0x25, 0x00][..], &CID_V1_DAG_PB].concat();
+    assert!(encoded.ends_with(&expected_link));
+    let decoded: Contact = serde_cbor::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, contact);
+}
+
+#[test]
+fn encode_link_like_go_ipld_prime() {
+    let cid = Cid::try_from(&CID_V1_DAG_PB[..]).unwrap();
+    let ipld = ipld!({ "details": Link(cid) });
+    // The bytes go-ipld-prime produces for the same value.
+    let expected = [
+        &[
+            0xa1, 0x67, 0x64, 0x65, 0x74, 0x61, 0x69, 0x6c, 0x73, 0xd8, 0x2a, 0x58, 0x25, 0x00,
+        ][..],
+        &CID_V1_DAG_PB,
+    ]
+    .concat();
+    assert_eq!(ipld_dag_cbor::to_vec(&ipld).unwrap(), expected);
+    assert_eq!(ipld_dag_cbor::from_slice(&expected).unwrap(), ipld);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Links {
+    #[serde(with = "ipld_dag_cbor::cid::option", default)]
+    maybe: Option<Cid>,
+    #[serde(with = "ipld_dag_cbor::cid::vec")]
+    list: Vec<Cid>,
+    #[serde(with = "ipld_dag_cbor::cid::map")]
+    map: BTreeMap<String, Cid>,
+}
+
+#[test]
+fn roundtrip_cid_containers() {
+    let cid = Cid::try_from(&CID_V1_DAG_PB[..]).unwrap();
+    let v0 = Cid::try_from(&CID_V1_DAG_PB[2..]).unwrap();
+    let mut map = BTreeMap::new();
+    map.insert("a".to_string(), cid.clone());
+    let links = Links {
+        maybe: Some(v0.clone()),
+        list: vec![cid.clone(), v0.clone()],
+        map,
+    };
+    let encoded = serde_cbor::to_vec(&links).unwrap();
+    // Every CID is a tag 42 link.
+    assert_eq!(
+        ipld_dag_cbor::from_slice(&encoded).unwrap(),
+        ipld!({
+            "maybe": Link(v0.clone()),
+            "list": [Link(cid.clone()), Link(v0)],
+            "map": { "a": Link(cid) },
+        })
+    );
+    let decoded: Links = serde_cbor::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, links);
+
+    let empty = Links {
+        maybe: None,
+        list: vec![],
+        map: BTreeMap::new(),
+    };
+    let encoded = serde_cbor::to_vec(&empty).unwrap();
+    assert_eq!(
+        ipld_dag_cbor::from_slice(&encoded).unwrap()["maybe"],
+        Ipld::Null
+    );
+    assert_eq!(serde_cbor::from_slice::<Links>(&encoded).unwrap(), empty);
+    // `{"list": [], "map": {}}`, without the optional field.
+    let missing = [
+        0xa2, 0x64, b'l', b'i', b's', b't', 0x80, 0x63, b'm', b'a', b'p', 0xa0,
+    ];
+    assert_eq!(serde_cbor::from_slice::<Links>(&missing).unwrap(), empty);
+}
+
+#[test]
+fn decode_invalid_cid_field() {
+    let mut encoded = serde_cbor::to_vec(&Contact {
+        name: "".to_string(),
+        details: Cid::try_from(&CID_V1_DAG_PB[..]).unwrap(),
+    })
+    .unwrap();
+    // Truncate the digest.
+    let len = encoded.len();
+    encoded[len - 38] = 0x24;
+    encoded.pop();
+    assert!(serde_cbor::from_slice::<Contact>(&encoded).is_err());
+}
+
+#[test]
+fn decode_real_link() {
+    // `{"details": bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi}`
+    let encoded = [
+        &[
+            0xa1, 0x67, 0x64, 0x65, 0x74, 0x61, 0x69, 0x6c, 0x73, 0xd8, 0x2a, 0x58, 0x25, 0x00,
+        ][..],
+        &CID_V1_DAG_PB,
+    ]
+    .concat();
+    let decoded = ipld_dag_cbor::from_slice(&encoded).unwrap();
+    assert_eq!(
+        decoded["details"].as_link().unwrap().to_bytes(),
+        CID_V1_DAG_PB
+    );
+    assert_eq!(ipld_dag_cbor::to_vec(&decoded).unwrap(), encoded);
+}
+
+#[test]
+fn decode_cid_field_without_multibase_prefix() {
+    let mut encoded = serde_cbor::to_vec(&Contact {
+        name: "".to_string(),
+        details: Cid::try_from(&CID_V1_DAG_PB[..]).unwrap(),
+    })
+    .unwrap();
+    // Drop the prefix and fix up the length of the byte string.
+    let len = encoded.len();
+    encoded.remove(len - 37);
+    encoded[len - 38] = 0x24;
+    assert!(serde_cbor::from_slice::<Contact>(&encoded).is_err());
+}
+
+#[test]
+fn decode_link_multibase_prefix() {
+    let fixtures: Vec<Vec<u8>> = vec![
+        // Bytes that don't start with the prefix.
+        vec![0xd8, 0x2a, 0x43, 0x07, 0x08, 0x09],
+        // A CID without the prefix.
+        [&[0xd8, 0x2a, 0x58, 0x24][..], &CID_V1_DAG_PB].concat(),
+        // An empty byte string.
+        vec![0xd8, 0x2a, 0x40],
+    ];
+    for bytes in fixtures {
+        assert_eq!(
+            ipld_dag_cbor::from_slice(&bytes).unwrap_err().to_string(),
+            "CID is missing the multibase identity prefix (0x00)"
+        );
+    }
+    // The prefix is stripped from a valid link.
+    let bytes = [&[0xd8, 0x2a, 0x58, 0x25, 0x00][..], &CID_V1_DAG_PB].concat();
+    assert_eq!(
+        ipld_dag_cbor::from_slice(&bytes).unwrap(),
+        Ipld::Link(Cid::try_from(&CID_V1_DAG_PB[..]).unwrap())
+    );
+}
+
+#[test]
+fn decode_link_typed() {
+    let encoded = [&[0xd8, 0x2a, 0x58, 0x25, 0x00][..], &CID_V1_DAG_PB].concat();
+    let cid = ipld_dag_cbor::from_slice(&encoded)
+        .unwrap()
+        .into_link()
+        .unwrap();
+    assert_eq!(cid.version(), Version::V1);
+    assert_eq!(cid.codec(), DAG_PB);
+    assert_eq!(cid.hash().code(), 0x12);
+
+    // The varint of the codec is cut off.
+    let malformed = [0xd8, 0x2a, 0x43, 0x00, 0x01, 0x80];
+    assert!(matches!(
+        ipld_dag_cbor::from_slice(&malformed).unwrap_err(),
+        ipld_dag_cbor::Error::InvalidCid(_)
+    ));
+}
+
+#[test]
+fn roundtrip_cid_v0_link() {
+    // `{"seq": 1, "prev": QmbWqxBEKC3P8tqsKc98xmWNzrzDtRLMiMPL8wBuTGsMnR}`, a CIDv0 is the bare
+    // SHA2-256 multihash behind the identity prefix.
+    let encoded = [
+        &[
+            0xa2, 0x63, 0x73, 0x65, 0x71, 0x01, 0x64, 0x70, 0x72, 0x65, 0x76, 0xd8, 0x2a, 0x58,
+            0x23, 0x00,
+        ][..],
+        &CID_V1_DAG_PB[2..],
+    ]
+    .concat();
+    let decoded = ipld_dag_cbor::from_slice(&encoded).unwrap();
+    let prev = decoded["prev"].as_link().unwrap();
+    assert_eq!(prev.version(), Version::V0);
+    assert_eq!(prev.codec(), DAG_PB);
+    assert_eq!(prev.to_bytes(), &CID_V1_DAG_PB[2..]);
+    assert_eq!(
+        prev.to_string(),
+        "QmbWqxBEKC3P8tqsKc98xmWNzrzDtRLMiMPL8wBuTGsMnR"
+    );
+    assert_eq!(ipld_dag_cbor::to_vec(&decoded).unwrap(), encoded);
+
+    let built = ipld!({
+        "seq": 1,
+        "prev": Link(Cid::from_str("QmbWqxBEKC3P8tqsKc98xmWNzrzDtRLMiMPL8wBuTGsMnR").unwrap()),
+    });
+    assert_eq!(ipld_dag_cbor::to_vec(&built).unwrap(), encoded);
+}
+
+#[test]
+fn decode_link_into_cid() {
+    let v0 = Cid::new_v0(Code::Sha2_256.digest(b"v0")).unwrap();
+    let v1 = Cid::try_from(&CID_V1_DAG_PB[..]).unwrap();
+    let identity = Cid::try_from(&[0x01, 0x71, 0x00, 0x00][..]).unwrap();
+    for cid in [v0, v1, identity] {
+        let bytes = cid.to_bytes();
+        let contents = ipld_dag_cbor::to_vec(&Ipld::Bytes([&[0x00][..], &bytes].concat())).unwrap();
+        let encoded = [&[0xd8, 0x2a][..], &contents].concat();
+        // The link carries the same bytes as the tag, without the identity prefix.
+        let decoded = ipld_dag_cbor::from_slice(&encoded).unwrap();
+        assert_eq!(decoded.as_link().unwrap().to_bytes(), bytes);
+        assert_eq!(decoded, Ipld::Link(cid.clone()));
+        assert_eq!(
+            ipld_dag_cbor::from_slice_borrowed(&encoded)
+                .unwrap()
+                .to_owned(),
+            decoded
+        );
+        assert_eq!(ipld_dag_cbor::to_vec(&decoded).unwrap(), encoded);
+    }
+    // Prefixed bytes that aren't a CID are no longer a link.
+    let invalid = [0xd8, 0x2a, 0x44, 0x00, 0x07, 0x08, 0x09];
+    assert!(matches!(
+        ipld_dag_cbor::from_slice(&invalid).unwrap_err(),
+        ipld_dag_cbor::Error::InvalidCid(_)
+    ));
+    assert!(ipld_dag_cbor::from_slice_borrowed(&invalid).is_err());
+}
+
+#[test]
+fn decode_cid_field_with_unexpected_tag() {
+    let mut encoded = serde_cbor::to_vec(&Contact {
+        name: "".to_string(),
+        details: Cid::try_from(&CID_V1_DAG_PB[..]).unwrap(),
+    })
+    .unwrap();
+    // Replace tag 42 with tag 258.
+    let len = encoded.len();
+    encoded.splice(len - 41..len - 39, vec![0xd9, 0x01, 0x02]);
+    let error = serde_cbor::from_slice::<Contact>(&encoded).unwrap_err();
+    assert!(error.to_string().contains("unexpected CBOR tag 258"));
+}
+
+#[test]
+fn cid_to_and_from_bytes() {
+    let cid = Cid::try_from(&CID_V1_DAG_PB[..]).unwrap();
+    let encoded = DagCbor::to_bytes(&cid).unwrap();
+    assert_eq!(
+        encoded,
+        [&[0xd8, 0x2a, 0x58, 0x25, 0x00][..], &CID_V1_DAG_PB].concat()
+    );
+    assert_eq!(Cid::from_bytes(&encoded).unwrap(), cid);
+
+    // A link to a truncated CID.
+    let invalid = [0xd8, 0x2a, 0x44, 0x00, 0x01, 0x70, 0x12];
+    assert!(matches!(
+        Cid::from_bytes(&invalid).unwrap_err(),
+        ipld_dag_cbor::Error::InvalidCid(_)
+    ));
+    assert!(matches!(
+        Cid::from_bytes(&[0x01]).unwrap_err(),
+        ipld_dag_cbor::Error::Codec(_)
+    ));
+}
+
+#[test]
+fn cid_of_value() {
+    // What `ipfs dag put` returns for `{}`.
+    let cid = ipld!({}).cid(Code::Sha2_256).unwrap();
+    assert_eq!(
+        cid.to_string(),
+        "bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua"
+    );
+    assert_eq!(cid.codec(), ipld_dag_cbor::DAG_CBOR);
+    assert_eq!(cid.hash().code(), multihash::SHA2_256);
+    assert_eq!(
+        cid.byte_len().unwrap(),
+        DagCbor::to_bytes(&cid).unwrap().len()
+    );
+
+    let value = ipld!({ "hello": "world", "link": Link(cid.clone()) });
+    let other = value.cid(Code::Sha2_512).unwrap();
+    assert_eq!(other.hash().code(), multihash::SHA2_512);
+    assert_eq!(other.hash().digest().len(), 64);
+    // The identity hash contains the encoded block.
+    let identity = value.cid(Code::Identity).unwrap();
+    assert_eq!(identity.hash().digest(), &value.to_bytes().unwrap()[..]);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct LegacyLinks {
+    #[serde(with = "ipld_dag_cbor::cid_bytes")]
+    parent: Vec<u8>,
+    #[serde(with = "ipld_dag_cbor::cid_bytes::option", default)]
+    previous: Option<Vec<u8>>,
+    #[serde(with = "ipld_dag_cbor::cid_bytes::vec")]
+    children: Vec<Vec<u8>>,
+}
+
+#[test]
+fn roundtrip_cid_bytes() {
+    let v0 = CID_V1_DAG_PB[2..].to_vec();
+    let legacy = LegacyLinks {
+        parent: CID_V1_DAG_PB.to_vec(),
+        previous: Some(v0.clone()),
+        children: vec![v0.clone(), CID_V1_DAG_PB.to_vec()],
+    };
+    let encoded = serde_cbor::to_vec(&legacy).unwrap();
+    // The fields are the same links `Ipld::Link` encodes to.
+    let cid = Cid::try_from(&CID_V1_DAG_PB[..]).unwrap();
+    let v0_cid = Cid::try_from(&v0[..]).unwrap();
+    assert_eq!(
+        ipld_dag_cbor::from_slice(&encoded).unwrap(),
+        ipld!({
+            "parent": Link(&cid),
+            "previous": Link(&v0_cid),
+            "children": [Link(&v0_cid), Link(&cid)],
+        })
+    );
+    let link = [&[0xd8, 0x2a, 0x58, 0x25, 0x00][..], &CID_V1_DAG_PB].concat();
+    assert!(encoded
+        .windows(link.len())
+        .any(|window| window == &link[..]));
+    assert_eq!(
+        serde_cbor::from_slice::<LegacyLinks>(&encoded).unwrap(),
+        legacy
+    );
+
+    // Bytes that aren't a CID can't be encoded as a link.
+    let invalid = LegacyLinks {
+        parent: vec![7, 8, 9],
+        previous: None,
+        children: vec![],
+    };
+    assert!(serde_cbor::to_vec(&invalid).is_err());
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "cid")]
+
+use std::str::FromStr;
+
+use ipld_core::Ipld;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Contact {
+    name: String,
+    #[serde(with = "ipld_dag_cbor::cid::compat")]
+    details: cid::Cid,
+}
+
+#[test]
+fn roundtrip_cid_crate_field() {
+    for string in &[
+        "QmbWqxBEKC3P8tqsKc98xmWNzrzDtRLMiMPL8wBuTGsMnR",
+        "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+    ] {
+        let details = cid::Cid::from_str(string).unwrap();
+        let contact = Contact {
+            name: "Hello World!".to_string(),
+            details,
+        };
+        let encoded = serde_cbor::to_vec(&contact).unwrap();
+        // The link is the same one an `Ipld::Link` encodes to.
+        let ipld = ipld_dag_cbor::from_slice(&encoded).unwrap();
+        assert_eq!(ipld["details"], Ipld::Link(details.into()));
+        let decoded: Contact = serde_cbor::from_slice(&encoded).unwrap();
+        assert_eq!(decoded, contact);
+    }
+}
@@ -1,7 +1,20 @@
 use std::collections::BTreeMap;
+use std::iter::FromIterator;
 
-use ipld_core::Ipld;
-use ipld_dag_cbor;
+use ipld_core::multihash::Code;
+use ipld_core::{ipld, Cid, Ipld, Multihash};
+use ipld_dag_cbor::{DagCbor, DecodeOptions, EncodeOptions, MapKeyOrder};
+use serde::{Deserialize, Serialize};
+
+/// A DAG-CBOR CID with an identity hash of a single byte.
+fn cid(byte: u8) -> Cid {
+    Cid::new_v1(0x71, Multihash::new(0x00, vec![byte]))
+}
+
+fn roundtrip(ipld: &Ipld) -> Ipld {
+    let encoded = ipld_dag_cbor::to_vec(ipld).unwrap();
+    ipld_dag_cbor::from_slice(&encoded).unwrap()
+}
 
 #[test]
 fn encode_struct() {
@@ -9,19 +22,1314 @@ fn encode_struct() {
     // Contact { name: "Hello World", details: CID }
     let mut map = BTreeMap::new();
     map.insert("name".to_string(), Ipld::String("Hello World!".to_string()));
-    map.insert("details".to_string(), Ipld::Link(vec![7, 8, 9]));
+    map.insert("details".to_string(), Ipld::Link(cid(7)));
     let contact = Ipld::Map(map);
 
-    let contact_encoded = ipld_dag_cbor::encode(&contact).unwrap();
+    let contact_encoded = ipld_dag_cbor::to_vec(&contact).unwrap();
     println!("encoded: {:02x?}", contact_encoded);
     let expected_encoded = vec![
-        0xa2, 0x67, 0x64, 0x65, 0x74, 0x61, 0x69, 0x6c, 0x73, 0xd8, 0x2a, 0x43, 0x07, 0x08, 0x09,
-        0x64, 0x6e, 0x61, 0x6d, 0x65, 0x6c, 0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x20, 0x57, 0x6f, 0x72,
-        0x6c, 0x64, 0x21,
+        0xa2, 0x64, 0x6e, 0x61, 0x6d, 0x65, 0x6c, 0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x20, 0x57, 0x6f,
+        0x72, 0x6c, 0x64, 0x21, 0x67, 0x64, 0x65, 0x74, 0x61, 0x69, 0x6c, 0x73, 0xd8, 0x2a, 0x46,
+        0x00, 0x01, 0x71, 0x00, 0x01, 0x07,
     ];
     println!("expected: {:02x?}", expected_encoded);
     assert_eq!(contact_encoded, expected_encoded);
+    assert_eq!(ipld_dag_cbor::encode(&contact).unwrap(), contact_encoded);
 
-    let contact_decoded: Ipld = ipld_dag_cbor::decode(&contact_encoded).unwrap();
+    let contact_decoded: Ipld = ipld_dag_cbor::from_slice(&contact_encoded).unwrap();
     assert_eq!(contact_decoded, contact);
 }
+
+#[test]
+fn roundtrip_scalars() {
+    let values = vec![
+        Ipld::Null,
+        Ipld::Bool(true),
+        Ipld::Bool(false),
+        Ipld::Integer(0),
+        Ipld::Integer(-1),
+        Ipld::Integer(i128::from(u64::MAX)),
+        Ipld::Integer(i128::from(i64::MIN)),
+        Ipld::Float(1.5),
+        Ipld::Float(-0.1),
+        Ipld::String("".to_string()),
+        Ipld::String("Hello World!".to_string()),
+        Ipld::Bytes(vec![]),
+        Ipld::Bytes(vec![1, 2, 3]),
+        Ipld::Link(cid(7)),
+    ];
+    for value in values {
+        assert_eq!(roundtrip(&value), value);
+    }
+}
+
+#[test]
+fn encode_bytes_as_byte_string() {
+    // Major type 2 with a length of 3, not an array of integers.
+    let encoded = ipld_dag_cbor::to_vec(&Ipld::Bytes(vec![1, 2, 3])).unwrap();
+    assert_eq!(encoded, vec![0x43, 0x01, 0x02, 0x03]);
+}
+
+#[test]
+fn roundtrip_collections() {
+    let list = Ipld::List(vec![Ipld::Integer(1), Ipld::Link(cid(1)), Ipld::Null]);
+    assert_eq!(roundtrip(&list), list);
+
+    let mut map = BTreeMap::new();
+    map.insert("link".to_string(), Ipld::Link(cid(2)));
+    map.insert("list".to_string(), list);
+    let map = Ipld::Map(map);
+    assert_eq!(roundtrip(&map), map);
+}
+
+#[test]
+fn roundtrip_nested() {
+    let mut ipld = Ipld::Link(cid(0));
+    for depth in 0..20 {
+        let mut map = BTreeMap::new();
+        map.insert(format!("level{}", depth), ipld);
+        map.insert("bytes".to_string(), Ipld::Bytes(vec![depth]));
+        ipld = Ipld::List(vec![Ipld::Map(map), Ipld::Link(cid(depth))]);
+    }
+    assert_eq!(roundtrip(&ipld), ipld);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Node {
+    #[serde(with = "ipld_dag_cbor")]
+    data: Ipld,
+}
+
+#[test]
+fn roundtrip_serde_with() {
+    let node = Node {
+        data: Ipld::List(vec![Ipld::Bytes(vec![1]), Ipld::Link(cid(2))]),
+    };
+    let encoded = serde_cbor::to_vec(&node).unwrap();
+    let decoded: Node = serde_cbor::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, node);
+}
+
+#[test]
+fn reencode_fixtures() {
+    let fixtures: Vec<(Vec<u8>, Ipld)> = vec![
+        (vec![0xf6], Ipld::Null),
+        (vec![0xf5], Ipld::Bool(true)),
+        (vec![0xf4], Ipld::Bool(false)),
+        (vec![0x18, 0x2a], Ipld::Integer(42)),
+        (vec![0x38, 0x29], Ipld::Integer(-42)),
+        (
+            vec![0xfb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a],
+            Ipld::Float(0.1),
+        ),
+        (vec![0x62, 0x68, 0x69], Ipld::String("hi".to_string())),
+        (vec![0x42, 0x01, 0x02], Ipld::Bytes(vec![1, 2])),
+        (
+            vec![0x82, 0x01, 0x82, 0x02, 0x03],
+            Ipld::List(vec![
+                Ipld::Integer(1),
+                Ipld::List(vec![Ipld::Integer(2), Ipld::Integer(3)]),
+            ]),
+        ),
+        (
+            vec![0xa1, 0x61, 0x61, 0x01],
+            Ipld::Map(BTreeMap::from_iter(vec![(
+                "a".to_string(),
+                Ipld::Integer(1),
+            )])),
+        ),
+        (
+            vec![0xd8, 0x2a, 0x46, 0x00, 0x01, 0x71, 0x00, 0x01, 0x07],
+            Ipld::Link(cid(7)),
+        ),
+    ];
+    for (bytes, expected) in fixtures {
+        let decoded = ipld_dag_cbor::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, expected);
+        assert_eq!(ipld_dag_cbor::to_vec(&decoded).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn to_bytes_from_bytes() {
+    let mut inner = BTreeMap::new();
+    inner.insert("link".to_string(), Ipld::Link(cid(3)));
+    let mut map = BTreeMap::new();
+    map.insert("inner".to_string(), Ipld::Map(inner));
+    map.insert("count".to_string(), Ipld::Integer(3));
+    let node = Ipld::Map(map);
+
+    let buf = node.to_bytes().unwrap();
+    assert_eq!(buf, ipld_dag_cbor::to_vec(&node).unwrap());
+    assert_eq!(Ipld::from_bytes(&buf).unwrap(), node);
+}
+
+fn map_with_keys(keys: &[&str]) -> Ipld {
+    let map = keys
+        .iter()
+        .enumerate()
+        .map(|(index, key)| (key.to_string(), Ipld::Integer(index as i128)));
+    Ipld::Map(BTreeMap::from_iter(map))
+}
+
+#[test]
+fn encode_map_keys_length_first() {
+    let map = map_with_keys(&["b", "aa", "a"]);
+    // Same bytes as go-ipfs produces for `{"b": 0, "aa": 1, "a": 2}`.
+    let expected = vec![
+        0xa3, 0x61, 0x61, 0x02, 0x61, 0x62, 0x00, 0x62, 0x61, 0x61, 0x01,
+    ];
+    assert_eq!(ipld_dag_cbor::to_vec(&map).unwrap(), expected);
+    assert_eq!(ipld_dag_cbor::from_slice(&expected).unwrap(), map);
+}
+
+#[test]
+fn encode_map_keys_bytewise() {
+    let map = map_with_keys(&["b", "aa", "a"]);
+    let options = EncodeOptions {
+        map_key_order: MapKeyOrder::Bytewise,
+        ..Default::default()
+    };
+    let expected = vec![
+        0xa3, 0x61, 0x61, 0x02, 0x62, 0x61, 0x61, 0x01, 0x61, 0x62, 0x00,
+    ];
+    assert_eq!(
+        ipld_dag_cbor::to_vec_with_options(&map, &options).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn encode_map_keys_shorter_first() {
+    // `"z"` sorts after `"aa"` bytewise, but DAG-CBOR wants shorter keys first.
+    let map = map_with_keys(&["a", "b", "aa", "z"]);
+    let encoded = ipld_dag_cbor::to_vec(&map).unwrap();
+    let expected = vec![
+        0xa4, 0x61, 0x61, 0x00, 0x61, 0x62, 0x01, 0x61, 0x7a, 0x03, 0x62, 0x61, 0x61, 0x02,
+    ];
+    assert_eq!(encoded, expected);
+}
+
+#[test]
+fn decode_non_string_key() {
+    // `{1: 2}`
+    let error = ipld_dag_cbor::from_slice(&[0xa1, 0x01, 0x02]).unwrap_err();
+    assert!(matches!(
+        error,
+        ipld_dag_cbor::Error::NonStringKey { offset: 1 }
+    ));
+    assert!(error.to_string().contains("map keys must be strings"));
+}
+
+fn strict() -> DecodeOptions {
+    DecodeOptions {
+        strict: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn decode_strict_ordered_keys() {
+    // `{"a": 1, "b": 2, "aa": 3}`
+    let bytes = [
+        0xa3, 0x61, 0x61, 0x01, 0x61, 0x62, 0x02, 0x62, 0x61, 0x61, 0x03,
+    ];
+    let decoded = ipld_dag_cbor::from_slice_with_options(&bytes, &strict()).unwrap();
+    assert_eq!(decoded, ipld_dag_cbor::from_slice(&bytes).unwrap());
+}
+
+#[test]
+fn decode_strict_reversed_keys() {
+    // `{"b": 2, "a": 1}`
+    let bytes = [0xa2, 0x61, 0x62, 0x02, 0x61, 0x61, 0x01];
+    assert!(ipld_dag_cbor::from_slice(&bytes).is_ok());
+    let error = ipld_dag_cbor::from_slice_with_options(&bytes, &strict()).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("map key \"a\" is not in canonical order"));
+}
+
+#[test]
+fn decode_strict_longer_key_first() {
+    // `{"aa": 1, "a": 2}` is bytewise ordered, but not length-first.
+    let bytes = [0xa2, 0x62, 0x61, 0x61, 0x01, 0x61, 0x61, 0x02];
+    assert!(ipld_dag_cbor::from_slice(&bytes).is_ok());
+    let error = ipld_dag_cbor::from_slice_with_options(&bytes, &strict()).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("map key \"a\" is not in canonical order"));
+}
+
+#[test]
+fn decode_duplicate_key() {
+    // `{"a": 1, "a": 2}`
+    let bytes = [0xa2, 0x61, 0x61, 0x01, 0x61, 0x61, 0x02];
+    match ipld_dag_cbor::from_slice(&bytes).unwrap_err() {
+        ipld_dag_cbor::Error::DuplicateKey { key, offset } => {
+            assert_eq!(key, "a");
+            assert_eq!(offset, 4);
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+}
+
+#[test]
+fn decode_duplicate_key_nested() {
+    // `[{"b": {"a": 1, "a": 2}}]`
+    let bytes = [
+        0x81, 0xa1, 0x61, 0x62, 0xa2, 0x61, 0x61, 0x01, 0x61, 0x61, 0x02,
+    ];
+    match ipld_dag_cbor::from_slice(&bytes).unwrap_err() {
+        ipld_dag_cbor::Error::DuplicateKey { key, offset } => {
+            assert_eq!(key, "a");
+            assert_eq!(offset, 8);
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+}
+
+#[test]
+fn deserialize_duplicate_key() {
+    // The validation pass of `from_slice` is skipped here, the visitor has to catch it.
+    let bytes = [0xa2, 0x61, 0x61, 0x01, 0x61, 0x61, 0x02];
+    let mut de = serde_cbor::Deserializer::from_slice(&bytes);
+    let error = ipld_dag_cbor::deserialize(&mut de).unwrap_err();
+    assert!(error.to_string().contains("duplicate map key \"a\""));
+}
+
+fn assert_indefinite_length(bytes: &[u8], expected_offset: usize) {
+    match ipld_dag_cbor::from_slice(bytes).unwrap_err() {
+        ipld_dag_cbor::Error::IndefiniteLength { offset } => assert_eq!(offset, expected_offset),
+        error => panic!("unexpected error: {}", error),
+    }
+}
+
+#[test]
+fn decode_indefinite_length() {
+    // An indefinite text string with a single chunk `"hi"`.
+    assert_indefinite_length(&[0x7f, 0x62, 0x68, 0x69, 0xff], 0);
+    // An indefinite byte string in two chunks, inside a list.
+    assert_indefinite_length(&[0x82, 0x01, 0x5f, 0x41, 0x01, 0x41, 0x02, 0xff], 2);
+    // `[_ 1, 2]`
+    assert_indefinite_length(&[0x9f, 0x01, 0x02, 0xff], 0);
+    // `{"a": {_ }}`
+    assert_indefinite_length(&[0xa1, 0x61, 0x61, 0xbf, 0xff], 3);
+}
+
+#[test]
+fn decode_indefinite_length_message() {
+    let error = ipld_dag_cbor::from_slice(&[0x9f, 0xff]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "error at byte 0: indefinite-length items are not allowed in DAG-CBOR"
+    );
+}
+
+#[test]
+fn decode_indefinite_length_everywhere() {
+    // `[1, [_ 2]]`, the inner list is indefinite.
+    let bytes = [0x82, 0x01, 0x9f, 0x02, 0xff];
+    for error in [
+        ipld_dag_cbor::from_slice(&bytes).unwrap_err(),
+        ipld_dag_cbor::from_slice_borrowed(&bytes).unwrap_err(),
+        ipld_dag_cbor::iter_from_slice(&bytes)
+            .next()
+            .unwrap()
+            .unwrap_err(),
+    ] {
+        match error {
+            ipld_dag_cbor::Error::IndefiniteLength { offset } => assert_eq!(offset, 2),
+            error => panic!("unexpected error: {}", error),
+        }
+    }
+}
+
+#[test]
+fn encode_integers_shortest_form() {
+    let fixtures: Vec<(i128, Vec<u8>)> = vec![
+        (23, vec![0x17]),
+        (24, vec![0x18, 0x18]),
+        (255, vec![0x18, 0xff]),
+        (256, vec![0x19, 0x01, 0x00]),
+        (65535, vec![0x19, 0xff, 0xff]),
+        (65536, vec![0x1a, 0x00, 0x01, 0x00, 0x00]),
+        (u32::MAX.into(), vec![0x1a, 0xff, 0xff, 0xff, 0xff]),
+        (
+            i128::from(u32::MAX) + 1,
+            vec![0x1b, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00],
+        ),
+        (
+            u64::MAX.into(),
+            vec![0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        ),
+        (-24, vec![0x37]),
+        (-25, vec![0x38, 0x18]),
+        (-256, vec![0x38, 0xff]),
+        (-257, vec![0x39, 0x01, 0x00]),
+    ];
+    for (integer, expected) in fixtures {
+        let encoded = ipld_dag_cbor::to_vec(&Ipld::Integer(integer)).unwrap();
+        assert_eq!(encoded, expected, "encoding {}", integer);
+    }
+}
+
+#[test]
+fn encode_integers_out_of_range() {
+    let max = i128::from(u64::MAX);
+    let min = i128::from(i64::MIN);
+    for integer in &[max + 1, min - 1, i128::MAX, i128::MIN] {
+        match ipld_dag_cbor::to_vec(&Ipld::Integer(*integer)).unwrap_err() {
+            ipld_dag_cbor::Error::IntegerOutOfRange { value, offset } => {
+                assert_eq!(value, *integer);
+                assert_eq!(offset, None);
+            }
+            error => panic!("unexpected error: {}", error),
+        }
+    }
+    assert!(ipld_dag_cbor::to_vec(&Ipld::Integer(max)).is_ok());
+    assert_eq!(
+        ipld_dag_cbor::to_vec(&Ipld::Integer(min)).unwrap(),
+        vec![0x3b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    );
+    // Nested integers are checked as well.
+    let list = Ipld::List(vec![Ipld::Integer(max + 1)]);
+    assert!(ipld_dag_cbor::to_vec(&list).is_err());
+}
+
+#[test]
+fn serialize_integers_out_of_range() {
+    #[derive(Serialize)]
+    struct Wrapper(#[serde(with = "ipld_dag_cbor")] Ipld);
+
+    let error = serde_cbor::to_vec(&Wrapper(Ipld::Integer(i128::from(i64::MIN) - 1))).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("integer -9223372036854775809 is outside the 64-bit range"));
+    assert!(serde_cbor::to_vec(&Wrapper(Ipld::Integer(i128::from(u64::MAX)))).is_ok());
+}
+
+#[test]
+fn decode_integers_out_of_range() {
+    // `-2^64`, CBOR's smallest negative integer.
+    let bytes = [0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    assert!(matches!(
+        ipld_dag_cbor::from_slice(&bytes).unwrap_err(),
+        ipld_dag_cbor::Error::IntegerOutOfRange {
+            value,
+            offset: Some(0),
+        } if value == -1 << 64
+    ));
+    let list = [&[0x81][..], &bytes].concat();
+    assert_eq!(
+        ipld_dag_cbor::from_slice(&list).unwrap_err().offset(),
+        Some(1)
+    );
+    assert!(ipld_dag_cbor::from_slice_borrowed(&bytes).is_err());
+
+    // Deserializing through Serde doesn't validate the bytes, the visitor checks the range.
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(with = "ipld_dag_cbor")] Ipld);
+    let error = serde_cbor::from_slice::<Wrapper>(&bytes)
+        .map(|wrapper| wrapper.0)
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("integer -18446744073709551616 is outside the 64-bit range"));
+
+    let min = [0x3b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    assert_eq!(
+        ipld_dag_cbor::from_slice(&min).unwrap(),
+        Ipld::Integer(i64::MIN.into())
+    );
+}
+
+#[test]
+fn encode_lengths_shortest_form() {
+    let encoded = ipld_dag_cbor::to_vec(&Ipld::Bytes(vec![0; 23])).unwrap();
+    assert_eq!(encoded[..1], [0x57]);
+    let encoded = ipld_dag_cbor::to_vec(&Ipld::Bytes(vec![0; 24])).unwrap();
+    assert_eq!(encoded[..2], [0x58, 0x18]);
+    let encoded = ipld_dag_cbor::to_vec(&Ipld::String("a".repeat(256))).unwrap();
+    assert_eq!(encoded[..3], [0x79, 0x01, 0x00]);
+    let encoded = ipld_dag_cbor::to_vec(&Ipld::List(vec![Ipld::Null; 255])).unwrap();
+    assert_eq!(encoded[..2], [0x98, 0xff]);
+    let encoded = ipld_dag_cbor::to_vec(&map_with_keys(&["a"])).unwrap();
+    assert_eq!(encoded[..1], [0xa1]);
+    let keys: Vec<String> = (0..65536).map(|index| index.to_string()).collect();
+    let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let encoded = ipld_dag_cbor::to_vec(&map_with_keys(&keys)).unwrap();
+    assert_eq!(encoded[..5], [0xba, 0x00, 0x01, 0x00, 0x00]);
+}
+
+fn assert_non_minimal(bytes: &[u8], expected_offset: usize, expected_value: u64) {
+    assert!(ipld_dag_cbor::from_slice(bytes).is_ok());
+    match ipld_dag_cbor::from_slice_with_options(bytes, &strict()).unwrap_err() {
+        ipld_dag_cbor::Error::NonMinimalEncoding { offset, value } => {
+            assert_eq!(offset, expected_offset);
+            assert_eq!(value, expected_value);
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+}
+
+#[test]
+fn decode_strict_non_minimal() {
+    // 5 as `0x18 0x05`.
+    assert_non_minimal(&[0x18, 0x05], 0, 5);
+    // `{"a": 10}` with 10 as `0x18 0x0a`.
+    assert_non_minimal(&[0xa1, 0x61, 0x61, 0x18, 0x0a], 3, 10);
+    // -5 as `0x38 0x04`, inside a list.
+    assert_non_minimal(&[0x82, 0x01, 0x38, 0x04], 2, 4);
+    // 255 as a 16-bit integer.
+    assert_non_minimal(&[0x19, 0x00, 0xff], 0, 255);
+    // `"hi"` with a one byte length.
+    assert_non_minimal(&[0x78, 0x02, 0x68, 0x69], 0, 2);
+    // A list of one element with a 32-bit length.
+    assert_non_minimal(&[0x9a, 0x00, 0x00, 0x00, 0x01, 0x01], 0, 1);
+}
+
+#[test]
+fn reencode_non_minimal() {
+    // Lenient decoding accepts 10 as `0x18 0x0a`, encoding always uses the shortest form.
+    let decoded = ipld_dag_cbor::from_slice(&[0x18, 0x0a]).unwrap();
+    assert_eq!(ipld_dag_cbor::to_vec(&decoded).unwrap(), vec![0x0a]);
+}
+
+#[test]
+fn decode_strict_minimal() {
+    let bytes = [
+        0x83, 0x18, 0x18, 0x39, 0x01, 0x00, 0x1a, 0x00, 0x01, 0x00, 0x00,
+    ];
+    let decoded = ipld_dag_cbor::from_slice_with_options(&bytes, &strict()).unwrap();
+    assert_eq!(
+        decoded,
+        Ipld::List(vec![
+            Ipld::Integer(24),
+            Ipld::Integer(-257),
+            Ipld::Integer(65536)
+        ])
+    );
+}
+
+#[test]
+fn encode_floats_64_bit() {
+    // The same bytes go-ipld-prime produces.
+    let fixtures: Vec<(f64, Vec<u8>)> = vec![
+        (
+            0.0,
+            vec![0xfb, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        ),
+        // Would fit into a half-precision float.
+        (
+            1.5,
+            vec![0xfb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        ),
+        (
+            0.1,
+            vec![0xfb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a],
+        ),
+    ];
+    for (float, expected) in fixtures {
+        let encoded = ipld_dag_cbor::to_vec(&Ipld::Float(float)).unwrap();
+        assert_eq!(encoded, expected, "encoding {}", float);
+        assert_eq!(
+            ipld_dag_cbor::from_slice(&encoded).unwrap(),
+            Ipld::Float(float)
+        );
+    }
+}
+
+#[test]
+fn encode_non_finite_float() {
+    for float in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY].iter() {
+        match ipld_dag_cbor::to_vec(&Ipld::Float(*float)).unwrap_err() {
+            ipld_dag_cbor::Error::InvalidFloat { value, offset } => {
+                assert_eq!(value.to_bits(), float.to_bits());
+                assert_eq!(offset, None);
+            }
+            error => panic!("unexpected error: {}", error),
+        }
+    }
+}
+
+#[test]
+fn encode_non_finite_float_allowed() {
+    let options = EncodeOptions {
+        allow_non_finite_floats: true,
+        ..Default::default()
+    };
+    let encoded = ipld_dag_cbor::to_vec_with_options(&Ipld::Float(f64::INFINITY), &options);
+    assert_eq!(
+        encoded.unwrap(),
+        vec![0xfb, 0x7f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+    );
+}
+
+#[test]
+fn decode_non_finite_float() {
+    let fixtures: Vec<Vec<u8>> = vec![
+        vec![0xf9, 0x7e, 0x00],
+        vec![0xf9, 0x7c, 0x00],
+        vec![0xf9, 0xfc, 0x00],
+        vec![0xfa, 0x7f, 0xc0, 0x00, 0x00],
+        vec![0xfa, 0x7f, 0x80, 0x00, 0x00],
+        vec![0xfa, 0xff, 0x80, 0x00, 0x00],
+        vec![0xfb, 0x7f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        vec![0xfb, 0x7f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        vec![0xfb, 0xff, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    ];
+    let expected = ["NaN", "inf", "-inf"];
+    for (index, bytes) in fixtures.iter().enumerate() {
+        assert!(ipld_dag_cbor::from_slice_with_options(bytes, &strict()).is_err());
+        let allow = DecodeOptions {
+            allow_non_finite_floats: true,
+            ..Default::default()
+        };
+        assert!(ipld_dag_cbor::from_slice_with_options(bytes, &allow).is_ok());
+        let error = ipld_dag_cbor::from_slice(bytes).unwrap_err();
+        assert!(matches!(
+            error,
+            ipld_dag_cbor::Error::InvalidFloat {
+                offset: Some(0),
+                ..
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "error at byte 0: {} is not allowed in DAG-CBOR",
+                expected[index % 3]
+            )
+        );
+    }
+}
+
+#[test]
+fn decode_narrow_floats() {
+    let fixtures: Vec<(Vec<u8>, Option<u8>)> = vec![
+        (vec![0xf9, 0x3e, 0x00], Some(16)),
+        (vec![0xfa, 0x3f, 0xc0, 0x00, 0x00], Some(32)),
+        (
+            vec![0xfb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            None,
+        ),
+    ];
+    for (bytes, narrow) in fixtures {
+        assert_eq!(ipld_dag_cbor::from_slice(&bytes).unwrap(), Ipld::Float(1.5));
+        let strict = ipld_dag_cbor::from_slice_with_options(&bytes, &strict());
+        match narrow {
+            Some(expected_bits) => match strict.unwrap_err() {
+                ipld_dag_cbor::Error::NonCanonicalFloat { bits, offset } => {
+                    assert_eq!(bits, expected_bits);
+                    assert_eq!(offset, 0);
+                }
+                error => panic!("unexpected error: {}", error),
+            },
+            None => assert_eq!(strict.unwrap(), Ipld::Float(1.5)),
+        }
+    }
+}
+
+#[test]
+fn decode_half_floats_widen_exactly() {
+    let fixtures = [
+        ([0x7b, 0xff], 65504.0),
+        ([0x00, 0x01], 2f64.powi(-24)),
+        ([0x03, 0xff], 2f64.powi(-14) - 2f64.powi(-24)),
+        ([0x80, 0x00], -0.0),
+    ];
+    for (half, expected) in fixtures.iter() {
+        // A list with the half-precision float as second item.
+        let bytes = [0x82, 0xf6, 0xf9, half[0], half[1]];
+        match ipld_dag_cbor::from_slice(&bytes).unwrap() {
+            Ipld::List(list) => match list[1] {
+                Ipld::Float(value) => {
+                    assert_eq!(value.to_bits(), expected.to_bits());
+                }
+                ref other => panic!("unexpected value: {:?}", other),
+            },
+            other => panic!("unexpected value: {:?}", other),
+        }
+        match ipld_dag_cbor::from_slice_with_options(&bytes, &strict()).unwrap_err() {
+            ipld_dag_cbor::Error::NonCanonicalFloat { bits, offset } => {
+                assert_eq!(bits, 16);
+                assert_eq!(offset, 2);
+            }
+            error => panic!("unexpected error: {}", error),
+        }
+    }
+}
+
+#[test]
+fn decode_link_without_multibase_prefix() {
+    let bytes = [0xd8, 0x2a, 0x43, 0x07, 0x08, 0x09];
+    assert!(ipld_dag_cbor::from_slice(&bytes).is_err());
+}
+
+fn assert_unsupported_simple_value(bytes: &[u8], expected_value: u8, expected_offset: usize) {
+    match ipld_dag_cbor::from_slice(bytes).unwrap_err() {
+        ipld_dag_cbor::Error::UnsupportedSimpleValue { value, offset } => {
+            assert_eq!(value, expected_value);
+            assert_eq!(offset, expected_offset);
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+}
+
+#[test]
+fn decode_undefined() {
+    assert_unsupported_simple_value(&[0xf7], 23, 0);
+    // `[1, undefined]`
+    assert_unsupported_simple_value(&[0x82, 0x01, 0xf7], 23, 2);
+    // `{"a": undefined}`
+    assert_unsupported_simple_value(&[0xa1, 0x61, 0x61, 0xf7], 23, 3);
+    assert_eq!(
+        ipld_dag_cbor::from_slice(&[0xf7]).unwrap_err().to_string(),
+        "error at byte 0: undefined is not supported in DAG-CBOR"
+    );
+    // Rejected in every mode and by every decoder, never mapped to `null`.
+    let strict = DecodeOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(matches!(
+        ipld_dag_cbor::from_slice_with_options(&[0xf7], &strict).unwrap_err(),
+        ipld_dag_cbor::Error::UnsupportedSimpleValue { value: 23, .. }
+    ));
+    assert!(matches!(
+        ipld_dag_cbor::from_slice_borrowed(&[0xf7]).unwrap_err(),
+        ipld_dag_cbor::Error::UnsupportedSimpleValue { value: 23, .. }
+    ));
+    assert!(ipld_dag_cbor::from_reader(&[0xf7][..]).is_err());
+    assert_eq!(ipld_dag_cbor::from_slice(&[0xf6]).unwrap(), Ipld::Null);
+}
+
+#[test]
+fn decode_unassigned_simple_values() {
+    assert_unsupported_simple_value(&[0xe0], 0, 0);
+    assert_unsupported_simple_value(&[0xf3], 19, 0);
+    assert_unsupported_simple_value(&[0x81, 0xf8, 0xff], 255, 1);
+}
+
+fn assert_unexpected_tag(bytes: &[u8], expected_tag: u64, expected_offset: usize) {
+    match ipld_dag_cbor::from_slice(bytes).unwrap_err() {
+        ipld_dag_cbor::Error::UnexpectedTag { tag, offset } => {
+            assert_eq!(tag, expected_tag);
+            assert_eq!(offset, expected_offset);
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+}
+
+#[test]
+fn decode_unexpected_tags() {
+    // Tag 0, a datetime string `"2013-03-21T20:04:00Z"`.
+    let mut datetime = vec![0xc0, 0x74];
+    datetime.extend_from_slice(b"2013-03-21T20:04:00Z");
+    assert_unexpected_tag(&datetime, 0, 0);
+    // Tag 2, the bignum 2^64.
+    assert_unexpected_tag(
+        &[
+            0xc2, 0x49, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+        2,
+        0,
+    );
+    // `{"a": [1(1363896240)]}`
+    assert_unexpected_tag(
+        &[0xa1, 0x61, 0x61, 0x81, 0xc1, 0x1a, 0x51, 0x4b, 0x67, 0xb0],
+        1,
+        4,
+    );
+}
+
+#[test]
+fn unexpected_tag_message() {
+    // `258([1])`, a set.
+    let tagged = [0xd9, 0x01, 0x02, 0x81, 0x01];
+    let error = ipld_dag_cbor::from_slice(&tagged).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "error at byte 0: unexpected CBOR tag 258 in DAG-CBOR"
+    );
+    // `{"data": 258([1])}` straight through Serde.
+    let node = [&[0xa1, 0x64, 0x64, 0x61, 0x74, 0x61][..], &tagged].concat();
+    let error = serde_cbor::from_slice::<Node>(&node).unwrap_err();
+    assert!(error.to_string().contains("unexpected CBOR tag 258"));
+}
+
+#[test]
+fn error_variants() {
+    use ipld_dag_cbor::Error;
+
+    // A truncated integer isn't well-formed CBOR.
+    assert!(matches!(
+        ipld_dag_cbor::from_slice(&[0x19, 0x01]).unwrap_err(),
+        Error::Cbor(_)
+    ));
+    // `{"b": 2, "a": 1}`
+    let bytes = [0xa2, 0x61, 0x62, 0x02, 0x61, 0x61, 0x01];
+    assert!(matches!(
+        ipld_dag_cbor::from_slice_with_options(&bytes, &strict()).unwrap_err(),
+        Error::UnorderedKey { offset: 4, .. }
+    ));
+    // `42(1)` is well-formed, but fails while building the `Ipld` link.
+    assert!(matches!(
+        ipld_dag_cbor::from_slice(&[0xd8, 0x2a, 0x01]).unwrap_err(),
+        Error::Codec(_)
+    ));
+    assert!(matches!(
+        ipld_dag_cbor::to_vec(&Ipld::Integer(i128::MAX)).unwrap_err(),
+        Error::IntegerOutOfRange { .. }
+    ));
+}
+
+struct FailingIo;
+
+impl std::io::Read for FailingIo {
+    fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("read failed"))
+    }
+}
+
+impl std::io::Write for FailingIo {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("write failed"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn reader_and_writer() {
+    let ipld = Ipld::List(vec![Ipld::Integer(1), Ipld::String("a".to_string())]);
+    let mut buffer = Vec::new();
+    ipld_dag_cbor::to_writer(&ipld, &mut buffer).unwrap();
+    assert_eq!(buffer, ipld_dag_cbor::to_vec(&ipld).unwrap());
+    assert_eq!(ipld_dag_cbor::from_reader(&buffer[..]).unwrap(), ipld);
+
+    assert!(matches!(
+        ipld_dag_cbor::to_writer(&ipld, FailingIo).unwrap_err(),
+        ipld_dag_cbor::Error::Io(_)
+    ));
+    assert!(matches!(
+        ipld_dag_cbor::from_reader(FailingIo).unwrap_err(),
+        ipld_dag_cbor::Error::Io(_)
+    ));
+}
+
+#[test]
+fn error_display() {
+    use ipld_dag_cbor::Error;
+
+    // A 16-bit integer that is missing a byte.
+    let cbor = ipld_dag_cbor::from_slice(&[0x19, 0x01]).unwrap_err();
+    assert_eq!(
+        cbor.to_string(),
+        "error at byte 2: EOF while parsing a value"
+    );
+    let errors = vec![
+        (
+            Error::Io(std::io::Error::other("broken pipe")),
+            "broken pipe",
+        ),
+        (Error::Codec("bytes expected".to_string()), "bytes expected"),
+        (
+            Error::InvalidCid("invalid varint".to_string()),
+            "invalid CID: invalid varint",
+        ),
+        (
+            Error::DuplicateKey {
+                key: "a".to_string(),
+                offset: 4,
+            },
+            "error at byte 4: duplicate map key \"a\"",
+        ),
+        (
+            Error::NonStringKey { offset: 1 },
+            "error at byte 1: map keys must be strings",
+        ),
+        (
+            Error::IndefiniteLength { offset: 0 },
+            "error at byte 0: indefinite-length items are not allowed in DAG-CBOR",
+        ),
+        (
+            Error::NonMinimalEncoding {
+                offset: 0,
+                value: 1,
+            },
+            "error at byte 0: 1 is not encoded in its shortest form",
+        ),
+        (
+            Error::InvalidFloat {
+                value: f64::NAN,
+                offset: None,
+            },
+            "NaN is not allowed in DAG-CBOR",
+        ),
+        (
+            Error::IntegerOutOfRange {
+                value: -1 << 64,
+                offset: None,
+            },
+            "integer -18446744073709551616 is outside the 64-bit range DAG-CBOR allows",
+        ),
+        (
+            Error::NonCanonicalFloat {
+                bits: 16,
+                offset: 2,
+            },
+            "error at byte 2: 16-bit float, DAG-CBOR requires 64-bit floats",
+        ),
+        (
+            Error::UnexpectedTag {
+                tag: 258,
+                offset: 0,
+            },
+            "error at byte 0: unexpected CBOR tag 258 in DAG-CBOR",
+        ),
+        (
+            Error::UnsupportedSimpleValue {
+                value: 23,
+                offset: 0,
+            },
+            "error at byte 0: undefined is not supported in DAG-CBOR",
+        ),
+    ];
+    for (error, expected) in errors {
+        assert_eq!(error.to_string(), expected);
+    }
+}
+
+#[test]
+fn error_offsets() {
+    // `{"a": [1, 2, 3], "b": "x"}`
+    let fixture = [
+        0xa2, 0x61, 0x61, 0x83, 0x01, 0x02, 0x03, 0x61, 0x62, 0x61, 0x78,
+    ];
+    assert!(ipld_dag_cbor::from_slice(&fixture).is_ok());
+
+    // Turn the `2` into tag 1.
+    let mut corrupted = fixture;
+    corrupted[5] = 0xc1;
+    let error = ipld_dag_cbor::from_slice(&corrupted).unwrap_err();
+    assert_eq!(error.offset(), Some(5));
+    assert_eq!(
+        error.to_string(),
+        "error at byte 5: unexpected CBOR tag 1 in DAG-CBOR"
+    );
+
+    // Turn the `"x"` into `undefined`.
+    let mut corrupted = fixture;
+    corrupted[9] = 0xf7;
+    let error = ipld_dag_cbor::from_slice(&corrupted).unwrap_err();
+    assert_eq!(error.offset(), Some(9));
+
+    // Truncate the input, the `"x"` is now longer than the rest of it.
+    let error = ipld_dag_cbor::from_slice(&fixture[..10]).unwrap_err();
+    assert_eq!(error.offset(), Some(9));
+    assert_eq!(
+        error.to_string(),
+        "error at byte 9: length 1 exceeds the remaining input"
+    );
+
+    assert_eq!(ipld_dag_cbor::Error::Codec("x".to_string()).offset(), None);
+}
+
+#[test]
+fn decode_forbidden_floats() {
+    let forbid = DecodeOptions {
+        forbid_floats: true,
+        ..Default::default()
+    };
+    // `[1.5]`
+    let finite = [0x81, 0xfb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    assert_eq!(
+        ipld_dag_cbor::from_slice(&finite).unwrap(),
+        Ipld::List(vec![Ipld::Float(1.5)])
+    );
+    let error = ipld_dag_cbor::from_slice_with_options(&finite, &forbid).unwrap_err();
+    assert!(matches!(
+        error,
+        ipld_dag_cbor::Error::FloatNotAllowed { offset: 1 }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "error at byte 1: floats not allowed in strict DAG-CBOR"
+    );
+
+    let nan = [0xfb, 0x7f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    assert!(ipld_dag_cbor::from_slice(&nan).is_err());
+    assert!(ipld_dag_cbor::from_slice_with_options(&nan, &forbid).is_err());
+}
+
+#[test]
+fn deserialize_non_finite_float() {
+    // `{"data": NaN}` straight through Serde.
+    let node = [0xa1, 0x64, 0x64, 0x61, 0x74, 0x61, 0xf9, 0x7e, 0x00];
+    let error = serde_cbor::from_slice::<Node>(&node).unwrap_err();
+    assert!(error.to_string().contains("NaN is not allowed in DAG-CBOR"));
+}
+
+#[test]
+fn deserialize_borrowed_non_finite_float() {
+    #[derive(Deserialize)]
+    struct BorrowedNode<'a> {
+        #[serde(deserialize_with = "ipld_dag_cbor::deserialize_borrowed", borrow)]
+        data: ipld_core::IpldRef<'a>,
+    }
+
+    let node = [0xa1, 0x64, 0x64, 0x61, 0x74, 0x61, 0xf9, 0x7e, 0x00];
+    let error = serde_cbor::from_slice::<BorrowedNode>(&node)
+        .map(|node| node.data)
+        .unwrap_err();
+    assert!(error.to_string().contains("NaN is not allowed in DAG-CBOR"));
+    // `{"data": Infinity}`
+    let node = [0xa1, 0x64, 0x64, 0x61, 0x74, 0x61, 0xf9, 0x7c, 0x00];
+    assert!(serde_cbor::from_slice::<BorrowedNode>(&node).is_err());
+}
+
+fn nested_lists(depth: usize) -> Vec<u8> {
+    let mut bytes = vec![0x81; depth];
+    bytes.push(0x01);
+    bytes
+}
+
+#[test]
+fn decode_max_depth() {
+    // 127 lists around an integer is 128 levels.
+    assert!(ipld_dag_cbor::from_slice(&nested_lists(127)).is_ok());
+    match ipld_dag_cbor::from_slice(&nested_lists(128)).unwrap_err() {
+        ipld_dag_cbor::Error::MaxDepthExceeded { limit, offset } => {
+            assert_eq!(limit, 128);
+            assert_eq!(offset, 127);
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+
+    let shallow = DecodeOptions {
+        max_depth: 3,
+        ..Default::default()
+    };
+    assert!(ipld_dag_cbor::from_slice_with_options(&nested_lists(2), &shallow).is_ok());
+    // `{"a": [1]}`
+    let map = [0xa1, 0x61, 0x61, 0x81, 0x01];
+    assert!(ipld_dag_cbor::from_slice_with_options(&map, &shallow).is_ok());
+    assert!(matches!(
+        ipld_dag_cbor::from_slice_with_options(&nested_lists(3), &shallow).unwrap_err(),
+        ipld_dag_cbor::Error::MaxDepthExceeded { limit: 3, .. }
+    ));
+}
+
+#[test]
+fn decode_deeply_nested() {
+    let error = ipld_dag_cbor::from_slice(&nested_lists(100_000)).unwrap_err();
+    assert!(matches!(
+        error,
+        ipld_dag_cbor::Error::MaxDepthExceeded { limit: 128, .. }
+    ));
+    let bytes = nested_lists(100_000);
+    assert!(ipld_dag_cbor::from_reader(&bytes[..]).is_err());
+    // Through Serde there is no validation pass, `serde_cbor` has a limit of its own.
+    let mut de = serde_cbor::Deserializer::from_slice(&bytes);
+    assert!(ipld_dag_cbor::deserialize(&mut de).is_err());
+}
+
+#[test]
+fn decode_max_collection_len() {
+    let options = DecodeOptions {
+        max_collection_len: 2,
+        ..Default::default()
+    };
+    // `[1, 2]` and `{"a": 1, "b": 2}`
+    assert!(ipld_dag_cbor::from_slice_with_options(&[0x82, 0x01, 0x02], &options).is_ok());
+    let map = [0xa2, 0x61, 0x61, 0x01, 0x61, 0x62, 0x02];
+    assert!(ipld_dag_cbor::from_slice_with_options(&map, &options).is_ok());
+
+    // `[[1, 2, 3]]`, rejected before the elements are read.
+    let bytes = [0x81, 0x83, 0x01, 0x02, 0x03];
+    match ipld_dag_cbor::from_slice_with_options(&bytes, &options).unwrap_err() {
+        ipld_dag_cbor::Error::CollectionTooLong {
+            length,
+            limit,
+            offset,
+        } => {
+            assert_eq!(length, 3);
+            assert_eq!(limit, 2);
+            assert_eq!(offset, 1);
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+    // `{"a": 1, "b": 2, "c": 3}`
+    let map = [0xa3, 0x61, 0x61, 0x01, 0x61, 0x62, 0x02, 0x61, 0x63, 0x03];
+    assert_eq!(
+        ipld_dag_cbor::from_slice_with_options(&map, &options)
+            .unwrap_err()
+            .to_string(),
+        "error at byte 0: 3 elements exceed the limit of 2 per list or map"
+    );
+}
+
+fn assert_length_exceeds_input(bytes: &[u8], expected_length: u64) {
+    match ipld_dag_cbor::from_slice(bytes).unwrap_err() {
+        ipld_dag_cbor::Error::LengthExceedsInput { length, offset } => {
+            assert_eq!(length, expected_length);
+            assert_eq!(offset, 0);
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+}
+
+#[test]
+fn decode_length_exceeds_input() {
+    // A byte string that declares 1 GiB, in an 11 byte buffer.
+    assert_length_exceeds_input(
+        &[
+            0x5a, 0x40, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+        ],
+        1 << 30,
+    );
+    // A list of 2^60 elements.
+    assert_length_exceeds_input(
+        &[0x9b, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01],
+        1 << 60,
+    );
+    // A map of two entries needs at least four bytes.
+    assert_length_exceeds_input(&[0xa2, 0x61, 0x61, 0x01], 2);
+    // A string that is one byte short.
+    assert_length_exceeds_input(&[0x63, 0x61, 0x62], 3);
+}
+
+#[test]
+fn decode_sequence() {
+    let values = vec![
+        Ipld::Integer(1),
+        Ipld::String("two".to_string()),
+        Ipld::List(vec![Ipld::Link(cid(7)), Ipld::Null]),
+    ];
+    let bytes: Vec<u8> = values
+        .iter()
+        .flat_map(|ipld| ipld_dag_cbor::to_vec(ipld).unwrap())
+        .collect();
+    let decoded: Vec<Ipld> = ipld_dag_cbor::iter_from_slice(&bytes)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(decoded, values);
+    assert_eq!(ipld_dag_cbor::iter_from_slice(&[]).count(), 0);
+}
+
+#[test]
+fn decode_tracking() {
+    let ipld = ipld!({ "a": [1, "two"], "b": Link(cid(7)) });
+    let encoded = ipld_dag_cbor::to_vec(&ipld).unwrap();
+    let mut buffer = encoded.clone();
+    // Garbage that isn't even well-formed CBOR.
+    buffer.extend_from_slice(&[0xff, 0x1c, 0x00]);
+    let (decoded, len) = ipld_dag_cbor::from_slice_tracking(&buffer).unwrap();
+    assert_eq!(decoded, ipld);
+    assert_eq!(len, encoded.len());
+    assert_eq!(
+        ipld_dag_cbor::from_slice_tracking(&[0x01, 0x02]).unwrap(),
+        (Ipld::Integer(1), 1)
+    );
+    assert!(ipld_dag_cbor::from_slice_tracking(&encoded[..len - 1]).is_err());
+}
+
+#[test]
+fn decode_sequence_errors() {
+    // `1` followed by a truncated list.
+    let mut iter = ipld_dag_cbor::iter_from_slice(&[0x01, 0x82, 0x01]);
+    assert_eq!(iter.next().unwrap().unwrap(), Ipld::Integer(1));
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+
+    // Offsets are relative to the whole sequence.
+    let mut iter =
+        ipld_dag_cbor::iter_from_slice(&[0x01, 0xa2, 0x61, 0x61, 0x01, 0x61, 0x61, 0x02]);
+    iter.next().unwrap().unwrap();
+    match iter.next().unwrap().unwrap_err() {
+        ipld_dag_cbor::Error::DuplicateKey { offset, .. } => assert_eq!(offset, 5),
+        error => panic!("unexpected error: {}", error),
+    }
+}
+
+#[test]
+fn patch_nested_field_and_reencode() {
+    let document = ipld!({
+        "meta": { "version": 1, "name": "doc" },
+        "items": [1, 2, 3],
+    });
+    let encoded = ipld_dag_cbor::to_vec(&document).unwrap();
+    let mut decoded = ipld_dag_cbor::from_slice(&encoded).unwrap();
+    *decoded.resolve_mut("meta/version").unwrap() = Ipld::Integer(2);
+    *decoded.get_mut("items").unwrap().get_index_mut(2).unwrap() = Ipld::Integer(4);
+    let reencoded = ipld_dag_cbor::to_vec(&decoded).unwrap();
+
+    // Both integers fit into the initial byte, so exactly two bytes differ.
+    assert_eq!(reencoded.len(), encoded.len());
+    let changed: Vec<_> = encoded
+        .iter()
+        .zip(&reencoded)
+        .filter(|(before, after)| before != after)
+        .map(|(_, after)| *after)
+        .collect();
+    assert_eq!(changed, vec![0x02, 0x04]);
+    assert_eq!(
+        decoded,
+        ipld!({
+            "meta": { "version": 2, "name": "doc" },
+            "items": [1, 2, 4],
+        })
+    );
+}
+
+#[test]
+fn check_canonical() {
+    let canonical = ipld_dag_cbor::to_vec(&ipld!({
+        "a": [1, -300, 1.5, "text", Bytes(vec![1]), null, true],
+        "bb": Link(cid(7)),
+    }))
+    .unwrap();
+    assert!(ipld_dag_cbor::is_canonical(&canonical).unwrap());
+
+    let non_canonical: Vec<&[u8]> = vec![
+        // `1` as a 16-bit integer.
+        &[0x19, 0x00, 0x01],
+        // A list whose length is a 8-bit integer.
+        &[0x98, 0x01, 0x01],
+        // `{"b": 1, "a": 2}`
+        &[0xa2, 0x61, 0x62, 0x01, 0x61, 0x61, 0x02],
+        // `{"aa": 1, "b": 2}`, bytewise but not length-first.
+        &[0xa2, 0x62, 0x61, 0x61, 0x01, 0x61, 0x62, 0x02],
+        // `[_ 1]`
+        &[0x9f, 0x01, 0xff],
+        // `1.5` as a 16-bit float.
+        &[0xf9, 0x3e, 0x00],
+        // `1` followed by a trailing `2`.
+        &[0x01, 0x02],
+    ];
+    for bytes in non_canonical {
+        assert!(
+            !ipld_dag_cbor::is_canonical(bytes).unwrap(),
+            "{:02x?}",
+            bytes
+        );
+    }
+
+    // Invalid data is an error.
+    assert!(ipld_dag_cbor::is_canonical(&[0xa2, 0x61, 0x61, 0x01, 0x61, 0x61, 0x02]).is_err());
+    assert!(ipld_dag_cbor::is_canonical(&[0x19, 0x01]).is_err());
+}
+
+#[test]
+fn recanonicalize() {
+    // `{"bb": 1, "a": [1.5]}` with the keys in the wrong order, a non-minimal integer and a
+    // 16-bit float.
+    let bytes = [
+        0xa2, 0x62, 0x62, 0x62, 0x19, 0x00, 0x01, 0x61, 0x61, 0x81, 0xf9, 0x3e, 0x00,
+    ];
+    assert!(!ipld_dag_cbor::is_canonical(&bytes).unwrap());
+    let canonical = ipld_dag_cbor::recanonicalize(&bytes).unwrap();
+    assert!(ipld_dag_cbor::is_canonical(&canonical).unwrap());
+    assert_eq!(
+        ipld_dag_cbor::from_slice(&canonical).unwrap(),
+        ipld!({ "a": [1.5], "bb": 1 })
+    );
+    assert_eq!(
+        ipld_dag_cbor::recanonicalize(&canonical).unwrap(),
+        canonical
+    );
+
+    // NaN is still rejected.
+    let nan = [0xfb, 0x7f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    assert!(matches!(
+        ipld_dag_cbor::recanonicalize(&nan).unwrap_err(),
+        ipld_dag_cbor::Error::InvalidFloat { .. }
+    ));
+}
+
+#[test]
+fn canonical_eq_matches_encodings() {
+    let nan = f64::from_bits(0x7ff8_0000_0000_0001);
+    let mut forward = BTreeMap::new();
+    forward.insert("a".to_string(), Ipld::Integer(1));
+    forward.insert("bb".to_string(), Ipld::Float(-0.0));
+    let mut backward = BTreeMap::new();
+    backward.insert("bb".to_string(), Ipld::Float(-0.0));
+    backward.insert("a".to_string(), Ipld::Integer(1));
+    let values = vec![
+        Ipld::Float(0.0),
+        Ipld::Float(-0.0),
+        Ipld::Float(f64::NAN),
+        Ipld::Float(nan),
+        Ipld::Integer(0),
+        Ipld::Integer(i128::MAX),
+        Ipld::Map(forward),
+        Ipld::Map(backward),
+        Ipld::Bytes(vec![1]),
+        Ipld::Link(cid(1)),
+        Ipld::List(vec![Ipld::Float(f64::INFINITY)]),
+    ];
+    for first in &values {
+        for second in &values {
+            let encodings = (ipld_dag_cbor::to_vec(first), ipld_dag_cbor::to_vec(second));
+            assert_eq!(
+                first.canonical_eq(second),
+                matches!(encodings, (Ok(first), Ok(second)) if first == second),
+                "{:?} and {:?}",
+                first,
+                second
+            );
+        }
+    }
+    assert!(!Ipld::Float(0.0).canonical_eq(&Ipld::Float(-0.0)));
+    // Equal, but without an encoding.
+    assert_eq!(Ipld::Float(f64::NAN), Ipld::Float(f64::NAN));
+    assert!(!Ipld::Float(f64::NAN).canonical_eq(&Ipld::Float(f64::NAN)));
+    assert!(!values[5].canonical_eq(&values[5]));
+    assert!(values[6].canonical_eq(&values[7]));
+}
+
+#[test]
+fn byte_len_matches_encoding() {
+    let long_string = "x".repeat(300);
+    let long_cid = Cid::new_v1(0x71, Code::Sha2_512.digest(b"x"));
+    let values = vec![
+        ipld!(null),
+        ipld!(true),
+        ipld!(23),
+        ipld!(24),
+        ipld!(-25),
+        ipld!(256),
+        ipld!(65536),
+        ipld!(u64::MAX),
+        ipld!(i64::MIN),
+        ipld!(1.5),
+        ipld!(""),
+        ipld!(long_string),
+        ipld!(Bytes(vec![0; 70000])),
+        ipld!(Link(cid(1))),
+        ipld!(Link(long_cid.clone())),
+        ipld!([1, [2, [3]], {}]),
+        ipld!({ "a": 1, "bb": [null, "x"], "link": Link(long_cid) }),
+    ];
+    for ipld in values {
+        assert_eq!(
+            ipld.byte_len().unwrap(),
+            ipld.to_bytes().unwrap().len(),
+            "{:?}",
+            ipld
+        );
+    }
+    // Values that can't be encoded fail just like encoding does.
+    assert!(matches!(
+        ipld!([1, { "a": f64::NAN }]).byte_len().unwrap_err(),
+        ipld_dag_cbor::Error::InvalidFloat { offset: None, .. }
+    ));
+    assert!(matches!(
+        Ipld::Integer(i128::MAX).byte_len().unwrap_err(),
+        ipld_dag_cbor::Error::IntegerOutOfRange { offset: None, .. }
+    ));
+}
@@ -0,0 +1,118 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use ipld_core::multihash::Code;
+use ipld_core::{Cid, Ipld};
+use ipld_dag_cbor::{Error, Link, Loader, DAG_CBOR};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Child {
+    value: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Parent {
+    name: String,
+    child: Link<Child>,
+}
+
+/// Keeps blocks in memory and counts how often they are loaded.
+#[derive(Default)]
+struct MemoryLoader {
+    blocks: HashMap<Cid, Vec<u8>>,
+    loads: RefCell<usize>,
+}
+
+impl MemoryLoader {
+    fn put<T: Serialize>(&mut self, value: &T) -> Link<T> {
+        let block = serde_cbor::to_vec(value).unwrap();
+        let cid = Cid::new_v1(DAG_CBOR, Code::Sha2_256.digest(&block));
+        self.blocks.insert(cid.clone(), block);
+        Link::new(cid)
+    }
+}
+
+impl Loader for MemoryLoader {
+    fn load(&self, cid: &Cid) -> Result<Vec<u8>, Error> {
+        *self.loads.borrow_mut() += 1;
+        self.blocks
+            .get(cid)
+            .cloned()
+            .ok_or_else(|| Error::Codec(format!("block {} not found", cid)))
+    }
+}
+
+#[test]
+fn resolve_typed_link() {
+    let mut loader = MemoryLoader::default();
+    let child = loader.put(&Child { value: 42 });
+    let parent = Parent {
+        name: "parent".to_string(),
+        child: child.clone(),
+    };
+    let root = loader.put(&parent);
+
+    let loaded = root.load(&loader).unwrap();
+    assert_eq!(loaded, parent);
+    assert_eq!(loaded.child.load(&loader).unwrap(), Child { value: 42 });
+    assert_eq!(*loader.loads.borrow(), 2);
+
+    // The link is a plain DAG-CBOR link and the CID can be read through it.
+    let block = &loader.blocks[&*root];
+    let ipld = ipld_dag_cbor::from_slice(block).unwrap();
+    assert_eq!(ipld["child"], Ipld::Link(child.clone().into_cid()));
+    assert_eq!(child.codec(), DAG_CBOR);
+}
+
+#[test]
+fn load_missing_or_mismatched_block() {
+    let mut loader = MemoryLoader::default();
+    let missing = Link::<Child>::new(Cid::new_v1(DAG_CBOR, Code::Sha2_256.digest(b"")));
+    assert_eq!(
+        missing.load(&loader).unwrap_err().to_string(),
+        format!("block {} not found", *missing)
+    );
+
+    // A block of another type doesn't decode.
+    let name = loader.put(&"name".to_string());
+    let wrong = Link::<Child>::new(name.into_cid());
+    assert!(wrong.load(&loader).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Versions {
+    #[serde(with = "ipld_dag_cbor::cid::option")]
+    current: Option<Cid>,
+    #[serde(with = "ipld_dag_cbor::cid::option")]
+    previous: Option<Cid>,
+    first: Option<Link<Child>>,
+    last: Option<Link<Child>>,
+}
+
+#[test]
+fn roundtrip_optional_links() {
+    let mut loader = MemoryLoader::default();
+    let child = loader.put(&Child { value: 1 });
+    let versions = Versions {
+        current: Some(child.clone().into_cid()),
+        previous: None,
+        first: Some(child.clone()),
+        last: None,
+    };
+    let encoded = serde_cbor::to_vec(&versions).unwrap();
+    // `None` is `null`, `Some` a link.
+    assert_eq!(
+        ipld_dag_cbor::from_slice(&encoded).unwrap(),
+        ipld_core::ipld!({
+            "current": Link(&*child),
+            "previous": null,
+            "first": Link(&*child),
+            "last": null,
+        })
+    );
+    assert_eq!(
+        serde_cbor::from_slice::<Versions>(&encoded).unwrap(),
+        versions
+    );
+}
@@ -0,0 +1,326 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use ipld_core::{decode_base64, Cid, Ipld};
+
+use crate::error::Error;
+use crate::MAX_DEPTH;
+
+/// A recursive descent parser that keeps track of the byte offset for errors.
+pub(crate) struct Parser<'a> {
+    input: &'a str,
+    offset: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            offset: 0,
+            depth: 1,
+        }
+    }
+
+    /// Parses a single value, only whitespace may follow it.
+    pub(crate) fn parse(mut self) -> Result<Ipld, Error> {
+        let ipld = self.value()?;
+        self.skip_whitespace();
+        if self.offset < self.input.len() {
+            return Err(self.syntax("trailing characters"));
+        }
+        Ok(ipld)
+    }
+
+    fn syntax(&self, message: &'static str) -> Error {
+        Error::Syntax {
+            message,
+            offset: self.offset,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.offset).copied()
+    }
+
+    fn next(&mut self) -> Result<u8, Error> {
+        let byte = self
+            .peek()
+            .ok_or_else(|| self.syntax("unexpected end of input"))?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn expect(&mut self, byte: u8, message: &'static str) -> Result<(), Error> {
+        self.skip_whitespace();
+        if self.peek() != Some(byte) {
+            return Err(self.syntax(message));
+        }
+        self.offset += 1;
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\n') | Some(b'\r') | Some(b'\t') = self.peek() {
+            self.offset += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<Ipld, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.literal("null", Ipld::Null),
+            Some(b't') => self.literal("true", Ipld::Bool(true)),
+            Some(b'f') => self.literal("false", Ipld::Bool(false)),
+            Some(b'"') => Ok(Ipld::String(self.string()?)),
+            Some(b'[') => self.nested(Self::list),
+            Some(b'{') => self.nested(Self::map),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.syntax("expected a value")),
+            None => Err(self.syntax("unexpected end of input")),
+        }
+    }
+
+    fn literal(&mut self, literal: &str, ipld: Ipld) -> Result<Ipld, Error> {
+        if !self.input[self.offset..].starts_with(literal) {
+            return Err(self.syntax("expected a value"));
+        }
+        self.offset += literal.len();
+        Ok(ipld)
+    }
+
+    fn nested<F>(&mut self, parse: F) -> Result<Ipld, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<Ipld, Error>,
+    {
+        if self.depth >= MAX_DEPTH {
+            return Err(Error::MaxDepthExceeded {
+                limit: MAX_DEPTH,
+                offset: self.offset,
+            });
+        }
+        self.depth += 1;
+        let ipld = parse(self);
+        self.depth -= 1;
+        ipld
+    }
+
+    fn list(&mut self) -> Result<Ipld, Error> {
+        self.offset += 1;
+        let mut list = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.offset += 1;
+            return Ok(Ipld::List(list));
+        }
+        loop {
+            list.push(self.value()?);
+            self.skip_whitespace();
+            match self.next()? {
+                b',' => {}
+                b']' => return Ok(Ipld::List(list)),
+                _ => {
+                    self.offset -= 1;
+                    return Err(self.syntax("expected `,` or `]`"));
+                }
+            }
+        }
+    }
+
+    fn map(&mut self) -> Result<Ipld, Error> {
+        let start = self.offset;
+        self.offset += 1;
+        let mut map = BTreeMap::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.offset += 1;
+            return Ok(Ipld::Map(map));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.syntax("expected a string key"));
+            }
+            let key_offset = self.offset;
+            let key = self.string()?;
+            self.expect(b':', "expected `:`")?;
+            let value = self.value()?;
+            match map.entry(key) {
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+                Entry::Occupied(entry) => {
+                    return Err(Error::DuplicateKey {
+                        key: entry.key().clone(),
+                        offset: key_offset,
+                    });
+                }
+            }
+            self.skip_whitespace();
+            match self.next()? {
+                b',' => {}
+                b'}' => break,
+                _ => {
+                    self.offset -= 1;
+                    return Err(self.syntax("expected `,` or `}`"));
+                }
+            }
+        }
+        reserved(map, start)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.offset += 1;
+        let mut string = String::new();
+        loop {
+            let start = self.offset;
+            while let Some(byte) = self.peek() {
+                if byte == b'"' || byte == b'\\' || byte < b' ' {
+                    break;
+                }
+                self.offset += 1;
+            }
+            // Only ASCII bytes stop the loop, so this is always on a character boundary.
+            string.push_str(&self.input[start..self.offset]);
+            match self.next()? {
+                b'"' => return Ok(string),
+                b'\\' => string.push(self.escape()?),
+                _ => {
+                    self.offset -= 1;
+                    return Err(self.syntax("control character in string"));
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, Error> {
+        let escape = match self.next()? {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let start = self.offset - 2;
+                let high = self.hex()?;
+                let code = if (0xd800..0xdc00).contains(&high) {
+                    if !self.input[self.offset..].starts_with("\\u") {
+                        return Err(self.syntax("unpaired surrogate"));
+                    }
+                    self.offset += 2;
+                    let low = self.hex()?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(self.syntax("unpaired surrogate"));
+                    }
+                    0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                } else {
+                    high
+                };
+                return std::char::from_u32(code).ok_or(Error::Syntax {
+                    message: "unpaired surrogate",
+                    offset: start,
+                });
+            }
+            _ => {
+                self.offset -= 1;
+                return Err(self.syntax("invalid escape"));
+            }
+        };
+        Ok(escape)
+    }
+
+    fn hex(&mut self) -> Result<u32, Error> {
+        // `from_str_radix` would accept a leading `+` as well.
+        let digits = self
+            .input
+            .get(self.offset..self.offset + 4)
+            .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .ok_or_else(|| self.syntax("invalid unicode escape"))?;
+        let code =
+            u32::from_str_radix(digits, 16).map_err(|_| self.syntax("invalid unicode escape"))?;
+        self.offset += 4;
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Ipld, Error> {
+        let start = self.offset;
+        if self.peek() == Some(b'-') {
+            self.offset += 1;
+        }
+        match self.peek() {
+            Some(b'0') => self.offset += 1,
+            Some(b'1'..=b'9') => self.digits(),
+            _ => return Err(self.syntax("invalid number")),
+        }
+        let mut float = false;
+        if self.peek() == Some(b'.') {
+            float = true;
+            self.offset += 1;
+            self.required_digits()?;
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            float = true;
+            self.offset += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.offset += 1;
+            }
+            self.required_digits()?;
+        }
+        let number = &self.input[start..self.offset];
+        if float {
+            let value: f64 = number.parse().expect("valid float syntax");
+            if !value.is_finite() {
+                return Err(Error::InvalidFloat(value));
+            }
+            Ok(Ipld::Float(value))
+        } else {
+            number
+                .parse()
+                .map(Ipld::Integer)
+                .map_err(|_| Error::IntegerOutOfRange { offset: start })
+        }
+    }
+
+    fn digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.offset += 1;
+        }
+    }
+
+    fn required_digits(&mut self) -> Result<(), Error> {
+        if let Some(b'0'..=b'9') = self.peek() {
+            self.digits();
+            Ok(())
+        } else {
+            Err(self.syntax("invalid number"))
+        }
+    }
+}
+
+/// Turns the reserved `{"/": …}` shapes into links and bytes.
+///
+/// A map whose only key is `"/"` must be one of them, maps with other keys next to `"/"` are
+/// plain maps.
+fn reserved(map: BTreeMap<String, Ipld>, offset: usize) -> Result<Ipld, Error> {
+    if map.len() != 1 {
+        return Ok(Ipld::Map(map));
+    }
+    match map.get("/") {
+        Some(Ipld::String(string)) => {
+            let cid = Cid::from_str(string)?;
+            Ok(Ipld::Link(cid))
+        }
+        Some(Ipld::Map(inner)) if inner.len() == 1 => match inner.get("bytes") {
+            Some(Ipld::String(string)) => decode_base64(string)
+                .map(Ipld::Bytes)
+                .ok_or(Error::InvalidBytes { offset }),
+            _ => Err(Error::InvalidReserved { offset }),
+        },
+        Some(_) => Err(Error::InvalidReserved { offset }),
+        None => Ok(Ipld::Map(map)),
+    }
+}
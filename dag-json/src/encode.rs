@@ -0,0 +1,81 @@
+use std::fmt::Write;
+
+use ipld_core::{encode_base64, Ipld};
+
+use crate::error::Error;
+use crate::EncodeOptions;
+
+pub(crate) fn write(ipld: &Ipld, options: &EncodeOptions, out: &mut String) -> Result<(), Error> {
+    match ipld {
+        Ipld::Null => out.push_str("null"),
+        Ipld::Bool(true) => out.push_str("true"),
+        Ipld::Bool(false) => out.push_str("false"),
+        Ipld::Integer(value) => write!(out, "{}", value).unwrap(),
+        Ipld::Float(value) => {
+            if !value.is_finite() {
+                if !options.allow_non_finite_floats {
+                    return Err(Error::InvalidFloat(*value));
+                }
+                out.push_str("null");
+                return Ok(());
+            }
+            // The debug output always contains a fraction or an exponent, so the number
+            // decodes as a float again.
+            write!(out, "{:?}", value).unwrap();
+        }
+        Ipld::String(string) => write_string(string, out),
+        Ipld::Bytes(bytes) => {
+            out.push_str(r#"{"/":{"bytes":""#);
+            out.push_str(&encode_base64(bytes));
+            out.push_str(r#""}}"#);
+        }
+        Ipld::List(list) => {
+            out.push('[');
+            for (index, item) in list.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write(item, options, out)?;
+            }
+            out.push(']');
+        }
+        Ipld::Map(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(first, _), (second, _)| {
+                (first.len(), first.as_bytes()).cmp(&(second.len(), second.as_bytes()))
+            });
+            out.push('{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write(value, options, out)?;
+            }
+            out.push('}');
+        }
+        Ipld::Link(cid) => {
+            write!(out, r#"{{"/":"{}"}}"#, cid).unwrap();
+        }
+    }
+    Ok(())
+}
+
+fn write_string(string: &str, out: &mut String) {
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
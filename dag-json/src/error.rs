@@ -0,0 +1,115 @@
+use std::fmt;
+
+use ipld_core::CidError;
+#[cfg(feature = "json")]
+use ipld_core::JsonError;
+
+/// Errors that can occur when encoding or decoding DAG-JSON.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The input isn't well-formed JSON.
+    Syntax {
+        /// What was expected or found.
+        message: &'static str,
+        /// The byte offset of the error.
+        offset: usize,
+    },
+    /// A map contains the same key more than once.
+    DuplicateKey {
+        /// The duplicated key.
+        key: String,
+        /// The byte offset of the second occurrence of the key.
+        offset: usize,
+    },
+    /// A number is an integer that doesn't fit into an `i128`.
+    IntegerOutOfRange {
+        /// The byte offset of the number.
+        offset: usize,
+    },
+    /// A float is NaN or infinite, which JSON can't represent.
+    InvalidFloat(f64),
+    /// Converting into a `serde_json::Value` failed, e.g. because an integer doesn't fit into
+    /// 64 bits, which is the most it can hold.
+    #[cfg(feature = "json")]
+    Json(JsonError),
+    /// A link isn't a valid CID.
+    InvalidCid(String),
+    /// The base64 of a `{"/": {"bytes": …}}` object is invalid.
+    InvalidBytes {
+        /// The byte offset of the object.
+        offset: usize,
+    },
+    /// A map has `"/"` as its only key, but isn't a link or bytes.
+    InvalidReserved {
+        /// The byte offset of the map.
+        offset: usize,
+    },
+    /// Values are nested deeper than the decoder allows.
+    MaxDepthExceeded {
+        /// The maximum depth.
+        limit: usize,
+        /// The byte offset of the list or map that exceeds the limit.
+        offset: usize,
+    },
+}
+
+impl Error {
+    /// Returns the byte offset in the input where the error occurred, if it is known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::Syntax { offset, .. }
+            | Error::DuplicateKey { offset, .. }
+            | Error::IntegerOutOfRange { offset }
+            | Error::InvalidBytes { offset }
+            | Error::InvalidReserved { offset }
+            | Error::MaxDepthExceeded { offset, .. } => Some(*offset),
+            Error::InvalidFloat(_) | Error::InvalidCid(_) => None,
+            #[cfg(feature = "json")]
+            Error::Json(_) => None,
+        }
+    }
+}
+
+/// Errors with a known offset are prefixed with it, like in `ipld-dag-cbor`.
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(offset) = self.offset() {
+            write!(fmt, "error at byte {}: ", offset)?;
+        }
+        match self {
+            Error::Syntax { message, .. } => write!(fmt, "{}", message),
+            Error::DuplicateKey { key, .. } => write!(fmt, "duplicate map key {:?}", key),
+            Error::IntegerOutOfRange { .. } => write!(fmt, "integer out of range"),
+            Error::InvalidFloat(value) => write!(fmt, "{} is not allowed in DAG-JSON", value),
+            #[cfg(feature = "json")]
+            Error::Json(error) => write!(fmt, "{}", error),
+            Error::InvalidCid(message) => write!(fmt, "invalid CID: {}", message),
+            Error::InvalidBytes { .. } => write!(fmt, "invalid base64 in bytes"),
+            Error::InvalidReserved { .. } => {
+                write!(fmt, "`/` must be a CID string or a bytes object")
+            }
+            Error::MaxDepthExceeded { limit, .. } => {
+                write!(fmt, "values are nested deeper than {} levels", limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<CidError> for Error {
+    fn from(error: CidError) -> Self {
+        Error::InvalidCid(error.to_string())
+    }
+}
+
+/// Non-finite floats are reported as [`Error::InvalidFloat`], like when encoding into a string.
+#[cfg(feature = "json")]
+impl From<JsonError> for Error {
+    fn from(error: JsonError) -> Self {
+        match error {
+            JsonError::NonFiniteFloat(value) => Error::InvalidFloat(value),
+            error => Error::Json(error),
+        }
+    }
+}
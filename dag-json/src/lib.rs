@@ -0,0 +1,71 @@
+//! An implementation of the DAG-JSON codec that doesn't depend on a JSON library.
+//!
+//! Links are encoded as `{"/": "<CID string>"}` and bytes as `{"/": {"bytes": "<base64>"}}`,
+//! with standard base64 without padding. That is what the DAG-JSON spec and the JavaScript
+//! implementation `@ipld/dag-json` use, not base64url. Decoding accepts padding and the base64url
+//! alphabet as well, with the same decoder as the `serde_json::Value` conversion of `ipld-core`.
+//!
+//! With the `json` feature, values can also be encoded into a `serde_json::Value`. This uses the
+//! conversion of `ipld-core`, which the feature enables as well.
+use ipld_core::Ipld;
+
+mod decode;
+mod encode;
+mod error;
+#[cfg(feature = "json")]
+mod value;
+
+pub use crate::error::Error;
+
+/// The maximum nesting depth of lists and maps when decoding, the same as DAG-CBOR's default.
+const MAX_DEPTH: usize = 128;
+
+/// Options for encoding DAG-JSON.
+#[derive(Clone, Debug, Default)]
+pub struct EncodeOptions {
+    /// Encode NaN and infinite floats as `null`, like JavaScript's `JSON.stringify`, instead of
+    /// returning an error. This loses information, the value decodes as [`Ipld::Null`].
+    pub allow_non_finite_floats: bool,
+}
+
+/// Encodes an `Ipld` value as canonical DAG-JSON.
+///
+/// The output has no whitespace and map keys are sorted like in DAG-CBOR, shorter keys first
+/// and keys of the same length bytewise.
+pub fn to_dag_json(ipld: &Ipld) -> Result<String, Error> {
+    to_dag_json_with_options(ipld, &EncodeOptions::default())
+}
+
+/// Encodes an `Ipld` value as canonical DAG-JSON with the given options.
+pub fn to_dag_json_with_options(ipld: &Ipld, options: &EncodeOptions) -> Result<String, Error> {
+    let mut out = String::new();
+    encode::write(ipld, options, &mut out)?;
+    Ok(out)
+}
+
+/// Encodes an `Ipld` value as a DAG-JSON shaped `serde_json::Value`.
+///
+/// The key order of the resulting objects is the one of `serde_json::Map`, serialize it with
+/// [`to_dag_json`] instead if the output needs to be canonical.
+#[cfg(feature = "json")]
+pub fn to_dag_json_value(ipld: &Ipld) -> Result<serde_json::Value, Error> {
+    to_dag_json_value_with_options(ipld, &EncodeOptions::default())
+}
+
+/// Encodes an `Ipld` value as a DAG-JSON shaped `serde_json::Value` with the given options.
+#[cfg(feature = "json")]
+pub fn to_dag_json_value_with_options(
+    ipld: &Ipld,
+    options: &EncodeOptions,
+) -> Result<serde_json::Value, Error> {
+    value::to_value(ipld, options)
+}
+
+/// Decodes an `Ipld` value from DAG-JSON.
+///
+/// Numbers with a fraction or an exponent become [`Ipld::Float`], all others
+/// [`Ipld::Integer`]. Objects with the single key `"/"` are decoded as links or bytes and must
+/// have one of the shapes above, objects with other keys next to `"/"` are plain maps.
+pub fn from_dag_json(string: &str) -> Result<Ipld, Error> {
+    decode::Parser::new(string).parse()
+}
@@ -0,0 +1,26 @@
+//! Encoding into a `serde_json::Value`, based on the conversion in `ipld-core`.
+use ipld_core::{Ipld, JsonOptions, WalkControl};
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::EncodeOptions;
+
+pub(crate) fn to_value(ipld: &Ipld, options: &EncodeOptions) -> Result<Value, Error> {
+    let mut ipld = ipld.clone();
+    if options.allow_non_finite_floats {
+        ipld.walk_mut(|_, value| {
+            if let Ipld::Float(float) = value {
+                if !float.is_finite() {
+                    *value = Ipld::Null;
+                }
+            }
+            WalkControl::Continue
+        });
+    }
+    // DAG-JSON allows all 64-bit integers, consumers that need safe integers must check them.
+    let options = JsonOptions {
+        allow_unsafe_integers: true,
+        ..Default::default()
+    };
+    Ok(ipld.into_json_with_options(&options)?)
+}
@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use ipld_core::{Cid, Ipld};
+use ipld_dag_json::{from_dag_json, to_dag_json, to_dag_json_with_options, EncodeOptions, Error};
+
+/// `bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi`
+const CID_V1_DAG_PB: [u8; 36] = [
+    0x01, 0x70, 0x12, 0x20, 0xc3, 0xc4, 0x73, 0x3e, 0xc8, 0xaf, 0xfd, 0x06, 0xcf, 0x9e, 0x9f, 0xf5,
+    0x0f, 0xfc, 0x6b, 0xcd, 0x2e, 0xc8, 0x5a, 0x61, 0x70, 0x00, 0x4b, 0xb7, 0x09, 0x66, 0x9c, 0x31,
+    0xde, 0x94, 0x39, 0x1a,
+];
+const CID_V1_STRING: &str = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+
+fn link() -> Ipld {
+    Ipld::Link(Cid::try_from(&CID_V1_DAG_PB[..]).unwrap())
+}
+
+fn map(entries: Vec<(&str, Ipld)>) -> Ipld {
+    Ipld::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn assert_roundtrip(ipld: &Ipld, json: &str) {
+    assert_eq!(to_dag_json(ipld).unwrap(), json);
+    assert_eq!(&from_dag_json(json).unwrap(), ipld);
+}
+
+#[test]
+fn roundtrip_scalars() {
+    assert_roundtrip(&Ipld::Null, "null");
+    assert_roundtrip(&Ipld::Bool(true), "true");
+    assert_roundtrip(&Ipld::Bool(false), "false");
+    assert_roundtrip(&Ipld::Integer(-42), "-42");
+    assert_roundtrip(&Ipld::Integer(i128::MAX), &i128::MAX.to_string());
+    assert_roundtrip(&Ipld::Float(1.0), "1.0");
+    assert_roundtrip(&Ipld::Float(-0.5), "-0.5");
+    assert_roundtrip(&Ipld::Float(1e300), "1e300");
+    assert_roundtrip(
+        &Ipld::String("\"quoted\"\n\u{1}ünïcödé".to_string()),
+        r#""\"quoted\"\n\u0001ünïcödé""#,
+    );
+}
+
+#[test]
+fn roundtrip_bytes() {
+    assert_roundtrip(&Ipld::Bytes(vec![]), r#"{"/":{"bytes":""}}"#);
+    // Standard base64 without padding, `-_` would be base64url.
+    assert_roundtrip(&Ipld::Bytes(vec![0xfb, 0xff]), r#"{"/":{"bytes":"+/8"}}"#);
+    assert_roundtrip(&Ipld::Bytes(vec![7, 8, 9]), r#"{"/":{"bytes":"BwgJ"}}"#);
+}
+
+#[test]
+fn roundtrip_nested_links_and_bytes() {
+    let ipld = map(vec![
+        ("link", link()),
+        (
+            "list",
+            Ipld::List(vec![
+                Ipld::Bytes(vec![1, 2, 3]),
+                map(vec![("inner", link())]),
+            ]),
+        ),
+    ]);
+    let json = format!(
+        r#"{{"link":{{"/":"{0}"}},"list":[{{"/":{{"bytes":"AQID"}}}},{{"inner":{{"/":"{0}"}}}}]}}"#,
+        CID_V1_STRING
+    );
+    assert_roundtrip(&ipld, &json);
+}
+
+#[test]
+fn canonical_key_order() {
+    let ipld = map(vec![
+        ("bb", Ipld::Integer(1)),
+        ("a", Ipld::Integer(2)),
+        ("aaa", Ipld::Integer(3)),
+        ("b", Ipld::Integer(4)),
+    ]);
+    assert_eq!(
+        to_dag_json(&ipld).unwrap(),
+        r#"{"a":2,"b":4,"bb":1,"aaa":3}"#
+    );
+}
+
+#[test]
+fn decode_whitespace_and_escapes() {
+    let json = " { \"a\" : [ 1 , 2.5e1 , \"\\u00e9\\ud83d\\ude00\\/\" ] } ";
+    assert_eq!(
+        from_dag_json(json).unwrap(),
+        map(vec![(
+            "a",
+            Ipld::List(vec![
+                Ipld::Integer(1),
+                Ipld::Float(25.0),
+                Ipld::String("é😀/".to_string()),
+            ])
+        )])
+    );
+}
+
+#[test]
+fn decode_padded_and_url_safe_base64() {
+    assert_eq!(
+        from_dag_json(r#"{"/":{"bytes":"+/8="}}"#).unwrap(),
+        Ipld::Bytes(vec![0xfb, 0xff])
+    );
+    assert_eq!(
+        from_dag_json(r#"{"/":{"bytes":"-_8"}}"#).unwrap(),
+        Ipld::Bytes(vec![0xfb, 0xff])
+    );
+}
+
+/// Outputs of the JavaScript implementation `@ipld/dag-json` for the same values.
+#[test]
+fn javascript_fixtures() {
+    assert_roundtrip(
+        &map(vec![("hello", Ipld::String("world".to_string()))]),
+        r#"{"hello":"world"}"#,
+    );
+    assert_roundtrip(
+        &map(vec![
+            ("a", Ipld::Integer(1)),
+            ("b", Ipld::List(vec![Ipld::Float(1.5), Ipld::Integer(-2)])),
+            ("c", Ipld::Null),
+        ]),
+        r#"{"a":1,"b":[1.5,-2],"c":null}"#,
+    );
+    assert_roundtrip(
+        &Ipld::Bytes((0..10).collect()),
+        r#"{"/":{"bytes":"AAECAwQFBgcICQ"}}"#,
+    );
+    assert_roundtrip(
+        &map(vec![
+            ("data", Ipld::Bytes(b"hello".to_vec())),
+            ("link", link()),
+        ]),
+        &format!(
+            r#"{{"data":{{"/":{{"bytes":"aGVsbG8"}}}},"link":{{"/":"{}"}}}}"#,
+            CID_V1_STRING
+        ),
+    );
+}
+
+#[test]
+fn decode_reserved_key_in_other_shapes() {
+    // With other keys next to it, `/` is a plain key.
+    assert_eq!(
+        from_dag_json(&format!(r#"{{"/":"{}","x":1}}"#, CID_V1_STRING)).unwrap(),
+        map(vec![
+            ("/", Ipld::String(CID_V1_STRING.to_string())),
+            ("x", Ipld::Integer(1))
+        ])
+    );
+    assert_eq!(
+        from_dag_json(r#"{"/":{"bytes":"AQID"},"x":1}"#).unwrap(),
+        map(vec![
+            ("/", map(vec![("bytes", Ipld::String("AQID".to_string()))])),
+            ("x", Ipld::Integer(1))
+        ])
+    );
+    // On its own, it must be a link or bytes.
+    for json in &[
+        r#"{"/":1}"#,
+        r#"{"/":null}"#,
+        r#"{"/":[]}"#,
+        r#"{"/":{}}"#,
+        r#"{"/":{"bytes":1}}"#,
+        r#"{"/":{"bytes":"AQID","other":1}}"#,
+        r#"{"/":{"other":"AQID"}}"#,
+    ] {
+        assert_eq!(
+            from_dag_json(json).unwrap_err(),
+            Error::InvalidReserved { offset: 0 },
+            "{}",
+            json
+        );
+    }
+    assert_eq!(
+        from_dag_json(r#"[0,{"/":true}]"#).unwrap_err().to_string(),
+        "error at byte 3: `/` must be a CID string or a bytes object"
+    );
+}
+
+/// The examples from the DAG-JSON spec.
+#[test]
+fn spec_fixtures() {
+    assert_eq!(
+        from_dag_json(r#"{"/":"bafyreidykglsfhoixmivffc5uwhcgshx4j465xwqntbmu43nb2dzqwfvae"}"#)
+            .unwrap()
+            .as_link()
+            .map(Cid::codec),
+        Some(0x71)
+    );
+    assert_roundtrip(
+        &Ipld::Bytes(b"dag-json".to_vec()),
+        r#"{"/":{"bytes":"ZGFnLWpzb24"}}"#,
+    );
+    // Integers and floats are told apart by a fraction or an exponent.
+    assert_eq!(from_dag_json("1").unwrap(), Ipld::Integer(1));
+    assert_eq!(from_dag_json("1.0").unwrap(), Ipld::Float(1.0));
+    assert_eq!(from_dag_json("1e0").unwrap(), Ipld::Float(1.0));
+    assert_eq!(from_dag_json("-0").unwrap(), Ipld::Integer(0));
+    assert_eq!(from_dag_json("-0.0").unwrap(), Ipld::Float(-0.0));
+    assert_eq!(
+        from_dag_json("18446744073709551615").unwrap(),
+        Ipld::Integer(u64::MAX.into())
+    );
+}
+
+#[test]
+fn decode_errors() {
+    assert_eq!(
+        from_dag_json(r#"{"a":1,"a":2}"#).unwrap_err(),
+        Error::DuplicateKey {
+            key: "a".to_string(),
+            offset: 7
+        }
+    );
+    assert_eq!(
+        from_dag_json("[1,]").unwrap_err(),
+        Error::Syntax {
+            message: "expected a value",
+            offset: 3
+        }
+    );
+    for escape in &[r#""\u+041""#, r#""\u-041""#, r#""\u 041""#] {
+        assert_eq!(
+            from_dag_json(escape).unwrap_err(),
+            Error::Syntax {
+                message: "invalid unicode escape",
+                offset: 3
+            }
+        );
+    }
+    assert_eq!(
+        from_dag_json("1 2").unwrap_err(),
+        Error::Syntax {
+            message: "trailing characters",
+            offset: 2
+        }
+    );
+    assert_eq!(
+        from_dag_json("1e999").unwrap_err(),
+        Error::InvalidFloat(f64::INFINITY)
+    );
+    assert_eq!(
+        from_dag_json(&format!("{}0", i128::MAX)).unwrap_err(),
+        Error::IntegerOutOfRange { offset: 0 }
+    );
+    assert_eq!(
+        from_dag_json(r#"[{"/":{"bytes":"A"}}]"#).unwrap_err(),
+        Error::InvalidBytes { offset: 1 }
+    );
+    assert!(matches!(
+        from_dag_json(r#"{"/":"bafy0"}"#).unwrap_err(),
+        Error::InvalidCid(_)
+    ));
+    assert_eq!(
+        from_dag_json(&"[".repeat(200)).unwrap_err(),
+        Error::MaxDepthExceeded {
+            limit: 128,
+            offset: 127
+        }
+    );
+}
+
+#[test]
+fn encode_errors() {
+    assert_eq!(
+        to_dag_json(&Ipld::Float(f64::NAN)).unwrap_err().to_string(),
+        "NaN is not allowed in DAG-JSON"
+    );
+}
+
+#[test]
+fn encode_non_finite_floats_as_null() {
+    let options = EncodeOptions {
+        allow_non_finite_floats: true,
+    };
+    let ipld = Ipld::List(vec![
+        Ipld::Float(f64::NAN),
+        Ipld::Float(f64::INFINITY),
+        Ipld::Float(f64::NEG_INFINITY),
+        Ipld::Float(0.5),
+    ]);
+    assert_eq!(
+        to_dag_json_with_options(&ipld, &options).unwrap(),
+        "[null,null,null,0.5]"
+    );
+    assert!(matches!(
+        to_dag_json(&ipld).unwrap_err(),
+        Error::InvalidFloat(value) if value.is_nan()
+    ));
+}
+
+#[test]
+fn error_display() {
+    assert_eq!(
+        Error::Syntax {
+            message: "trailing characters",
+            offset: 2
+        }
+        .to_string(),
+        "error at byte 2: trailing characters"
+    );
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "json")]
+use std::convert::TryFrom;
+
+use ipld_core::{ipld, Cid, Ipld, JsonError};
+use ipld_dag_json::{
+    from_dag_json, to_dag_json, to_dag_json_value, to_dag_json_value_with_options, EncodeOptions,
+    Error,
+};
+use serde_json::json;
+
+/// `bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi`
+const CID_V1_DAG_PB: [u8; 36] = [
+    0x01, 0x70, 0x12, 0x20, 0xc3, 0xc4, 0x73, 0x3e, 0xc8, 0xaf, 0xfd, 0x06, 0xcf, 0x9e, 0x9f, 0xf5,
+    0x0f, 0xfc, 0x6b, 0xcd, 0x2e, 0xc8, 0x5a, 0x61, 0x70, 0x00, 0x4b, 0xb7, 0x09, 0x66, 0x9c, 0x31,
+    0xde, 0x94, 0x39, 0x1a,
+];
+
+#[test]
+fn value_matches_string() {
+    let ipld = ipld!({
+        "bytes": Bytes(vec![0xfb, 0xff]),
+        "link": Link(Cid::try_from(&CID_V1_DAG_PB[..]).unwrap()),
+        "list": [1, -2.5, "three", null, true],
+    });
+    let value = to_dag_json_value(&ipld).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "bytes": {"/": {"bytes": "+/8"}},
+            "link": {"/": "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"},
+            "list": [1, -2.5, "three", null, true],
+        })
+    );
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&to_dag_json(&ipld).unwrap()).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn value_errors() {
+    assert_eq!(
+        to_dag_json_value(&Ipld::Integer(i128::from(u64::MAX) + 1)).unwrap_err(),
+        Error::Json(JsonError::IntegerOutOfRange(i128::from(u64::MAX) + 1))
+    );
+    assert!(matches!(
+        to_dag_json_value(&Ipld::Float(f64::INFINITY)).unwrap_err(),
+        Error::InvalidFloat(_)
+    ));
+    let options = EncodeOptions {
+        allow_non_finite_floats: true,
+    };
+    assert_eq!(
+        to_dag_json_value_with_options(&Ipld::Float(f64::INFINITY), &options).unwrap(),
+        serde_json::Value::Null
+    );
+}
+
+#[test]
+fn bytes_decode_like_ipld_core() {
+    for bytes in &["+/8", "+/8=", "-_8", "_w", "/w", "/x", "+/8==="] {
+        let json = format!(r#"{{"/":{{"bytes":"{}"}}}}"#, bytes);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            from_dag_json(&json).ok(),
+            Ipld::try_from(value).ok(),
+            "decoding {}",
+            bytes
+        );
+    }
+    assert_eq!(
+        from_dag_json(r#"{"/":{"bytes":"_w"}}"#).unwrap(),
+        Ipld::Bytes(vec![0xff])
+    );
+}